@@ -0,0 +1,168 @@
+//! Compares parsing a `NomBytes`, or a borrowed `NomBytesRef`, to parsing a
+//! plain `&[u8]` with the same parsers, on some typical `nom` workloads.
+//!
+//! Run with `cargo bench`.
+
+use bytes::Bytes;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use nom::bytes::complete::{tag, take_until, take_while1};
+use nom::character::complete::{digit1, line_ending, space0};
+use nom::combinator::{map, recognize};
+use nom::error::ParseError;
+use nom::multi::fold_many0;
+use nom::sequence::{separated_pair, terminated, tuple};
+use nom::{
+    AsBytes, Compare, FindSubstring, IResult, InputIter, InputLength, InputTake,
+    InputTakeAtPosition, Offset, Slice,
+};
+use nombytes::NomBytes;
+use std::ops::{Range, RangeFrom, RangeTo};
+
+/// `key = value` lines, like a simple config file.
+fn config_input(lines: usize) -> String {
+    (0..lines)
+        .map(|i| format!("key_{i} = value number {i}\n"))
+        .collect()
+}
+
+/// Whitespace separated numbers, many short tokens.
+fn numbers_input(count: usize) -> String {
+    (0..count).map(|i| format!("{} ", i * 7919)).collect()
+}
+
+/// Everything the parsers below need from their input, so the very same
+/// parsers run on `&[u8]`, `NomBytes` and `NomBytesRef`.
+trait Input:
+    Clone
+    + AsBytes
+    + InputLength
+    + InputTake
+    + InputIter<Item = u8>
+    + InputTakeAtPosition<Item = u8>
+    + Offset
+    + Slice<Range<usize>>
+    + Slice<RangeFrom<usize>>
+    + Slice<RangeTo<usize>>
+    + for<'a> Compare<&'a str>
+    + FindSubstring<&'static str>
+{
+}
+
+impl<I> Input for I where
+    I: Clone
+        + AsBytes
+        + InputLength
+        + InputTake
+        + InputIter<Item = u8>
+        + InputTakeAtPosition<Item = u8>
+        + Offset
+        + Slice<Range<usize>>
+        + Slice<RangeFrom<usize>>
+        + Slice<RangeTo<usize>>
+        + for<'a> Compare<&'a str>
+        + FindSubstring<&'static str>
+{
+}
+
+fn config<I: Input, E: ParseError<I>>(input: I) -> IResult<I, usize, E> {
+    // Counts the pairs, as `many0` would need nom's `alloc` feature
+    fold_many0(
+        terminated(
+            separated_pair(
+                take_while1(|b: u8| b.is_ascii_alphanumeric() || b == b'_'),
+                tuple((space0, tag("="), space0)),
+                take_until("\n"),
+            ),
+            line_ending,
+        ),
+        || 0,
+        |count, _| count + 1,
+    )(input)
+}
+
+fn numbers<I: Input, E: ParseError<I>>(input: I) -> IResult<I, usize, E> {
+    fold_many0(
+        terminated(
+            map(recognize(digit1), |digits: I| digits.as_bytes().len()),
+            space0,
+        ),
+        || 0,
+        |total, len| total + len,
+    )(input)
+}
+
+fn bench_config(c: &mut Criterion) {
+    let input = config_input(10_000);
+    let nb = NomBytes::new(Bytes::from(input.clone()));
+
+    let mut group = c.benchmark_group("config");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("&[u8]", |b| {
+        b.iter(|| config::<_, ()>(black_box(input.as_bytes())).unwrap());
+    });
+    group.bench_function("NomBytes", |b| {
+        b.iter(|| config::<_, ()>(black_box(nb.clone())).unwrap());
+    });
+    group.bench_function("NomBytesRef", |b| {
+        b.iter(|| config::<_, ()>(black_box(nb.as_nom_bytes_ref())).unwrap());
+    });
+    group.finish();
+}
+
+fn bench_numbers(c: &mut Criterion) {
+    let input = numbers_input(50_000);
+    let nb = NomBytes::new(Bytes::from(input.clone()));
+
+    let mut group = c.benchmark_group("numbers");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("&[u8]", |b| {
+        b.iter(|| numbers::<_, ()>(black_box(input.as_bytes())).unwrap());
+    });
+    group.bench_function("NomBytes", |b| {
+        b.iter(|| numbers::<_, ()>(black_box(nb.clone())).unwrap());
+    });
+    group.bench_function("NomBytesRef", |b| {
+        b.iter(|| numbers::<_, ()>(black_box(nb.as_nom_bytes_ref())).unwrap());
+    });
+    group.finish();
+}
+
+fn bench_slicing(c: &mut Criterion) {
+    let input = numbers_input(1_000);
+    let nb = NomBytes::new(Bytes::from(input.clone()));
+
+    // Slicing off one byte at a time, the worst case for per-slice overhead
+    let mut group = c.benchmark_group("slicing");
+    group.throughput(Throughput::Elements(input.len() as u64));
+    group.bench_function("&[u8]", |b| {
+        b.iter(|| {
+            let mut rest = black_box(input.as_bytes());
+            while !rest.is_empty() {
+                rest = black_box(rest.slice(1..));
+            }
+            rest
+        });
+    });
+    group.bench_function("NomBytes", |b| {
+        b.iter(|| {
+            let mut rest = black_box(nb.clone());
+            while rest.input_len() > 0 {
+                rest = black_box(rest.slice(1..));
+            }
+            rest
+        });
+    });
+    group.bench_function("NomBytesRef", |b| {
+        b.iter(|| {
+            let mut rest = black_box(nb.as_nom_bytes_ref());
+            while rest.input_len() > 0 {
+                rest = black_box(rest.slice(1..));
+            }
+            rest
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_config, bench_numbers, bench_slicing);
+criterion_main!(benches);
@@ -0,0 +1,324 @@
+use crate::{NomBytes, NomStr};
+use ariadne::{Cache, Source};
+use core::fmt::{Debug, Display};
+use core::ops::Range;
+
+/// An [`ariadne::Cache`] holding a single [`NomBytes`] source.
+///
+/// `ariadne` wants its sources as text, so a source that is valid UTF-8 is
+/// shared with the `NomBytes` it came from rather than copied. One that
+/// isn't is decoded lossily into a copy, which means offsets past the first
+/// invalid sequence can be off by a few bytes.
+///
+/// # Examples
+///
+/// ```
+/// use ariadne::{Label, Report, ReportKind};
+/// use nombytes::{NomBytes, NomSource};
+///
+/// let input = NomBytes::from("let x = 12");
+/// let mut cache = NomSource::new("main.txt", &input);
+///
+/// // A copy of part of `input` has no span in it
+/// let name = NomBytes::from(&input.as_bytes()[4..5]);
+/// assert_eq!(cache.span(&name), None);
+///
+/// let name = NomBytes::new(input.to_bytes().slice(4..5));
+/// let span = cache.span(&name).unwrap();
+/// assert_eq!(span, ("main.txt", 4..5));
+///
+/// let mut output = Vec::new();
+/// Report::build(ReportKind::Advice, span.clone())
+///     .with_message("unused variable")
+///     .with_label(Label::new(span).with_message("never read"))
+///     .finish()
+///     .write(&mut cache, &mut output)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct NomSource<Id> {
+    id: Id,
+    bytes: NomBytes,
+    source: Source<NomStr>,
+}
+
+impl<Id> NomSource<Id> {
+    /// Creates a new `NomSource` called `id` holding `source`.
+    pub fn new(id: Id, source: &NomBytes) -> Self {
+        Self {
+            id,
+            bytes: source.clone(),
+            source: Source::from(NomStr::from_utf8_lossy(source.clone())),
+        }
+    }
+
+    /// Returns the id of the source.
+    #[inline]
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Returns the source this was created from.
+    #[inline]
+    pub fn as_nom_bytes(&self) -> &NomBytes {
+        &self.bytes
+    }
+
+    /// Returns the span that `slice` covers in this source, in the form
+    /// `ariadne`'s [`Span`](ariadne::Span) is implemented for, or `None` if
+    /// `slice` isn't a slice of it.
+    pub fn span(&self, slice: &NomBytes) -> Option<(Id, Range<usize>)>
+    where
+        Id: Clone,
+    {
+        let start = self.bytes.try_offset(slice)?;
+        Some((self.id.clone(), start..start + slice.as_bytes().len()))
+    }
+}
+
+impl<Id: Display + Eq> Cache<Id> for NomSource<Id> {
+    type Storage = NomStr;
+
+    fn fetch(&mut self, id: &Id) -> Result<&Source<NomStr>, impl Debug> {
+        if id == &self.id {
+            Ok(&self.source)
+        } else {
+            Err(format!("Failed to fetch source '{id}'"))
+        }
+    }
+
+    fn display<'a>(&self, id: &'a Id) -> Option<impl Display + 'a> {
+        Some(id)
+    }
+}
+
+#[cfg(feature = "nom7")]
+mod nom7 {
+    use super::NomSource;
+    use crate::labels::{self, Labels, FAILED, INCOMPLETE};
+    use ariadne::{Config, IndexType, Label, Report, ReportBuilder, ReportKind};
+    use core::fmt::Debug;
+    use core::hash::Hash;
+    use core::ops::Range;
+
+    impl<Id> NomSource<Id>
+    where
+        Id: Debug + Hash + Eq + Clone,
+    {
+        /// Starts an error [`Report`] for `err`, which was returned by a
+        /// parser that was given this source, with labels at the byte offsets
+        /// the error and any contexts it was given point at.
+        ///
+        /// The report is configured to use byte offsets, so if you replace
+        /// its [`Config`], keep [`IndexType::Byte`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use nom::character::complete::digit1;
+        /// use nom::error::{context, VerboseError};
+        /// use nom::IResult;
+        /// use nombytes::{NomBytes, NomSource};
+        ///
+        /// fn number(input: NomBytes) -> IResult<NomBytes, NomBytes, VerboseError<NomBytes>> {
+        ///     context("number", digit1)(input)
+        /// }
+        ///
+        /// let input = NomBytes::from("abc");
+        /// let mut cache = NomSource::new("number.txt", &input);
+        /// let report = cache
+        ///     .build_report(number(input.clone()).unwrap_err())
+        ///     .with_help("numbers are made of digits")
+        ///     .finish();
+        ///
+        /// let mut output = Vec::new();
+        /// report.write(&mut cache, &mut output)?;
+        /// # Ok::<(), std::io::Error>(())
+        /// ```
+        pub fn build_report<E: Labels>(
+            &self,
+            err: nom::Err<E>,
+        ) -> ReportBuilder<'static, (Id, Range<usize>)> {
+            let (message, labels) = match err {
+                nom::Err::Error(e) | nom::Err::Failure(e) => (FAILED, e.labels(&self.bytes)),
+                nom::Err::Incomplete(needed) => {
+                    (INCOMPLETE, vec![labels::incomplete(needed, &self.bytes)])
+                }
+            };
+
+            let location = labels.first().map_or(0..0, |(span, _)| span.clone());
+            Report::build(ReportKind::Error, (self.id.clone(), location))
+                .with_config(Config::default().with_index_type(IndexType::Byte))
+                .with_message(message)
+                .with_labels(
+                    labels.into_iter().map(|(span, label)| {
+                        Label::new((self.id.clone(), span)).with_message(label)
+                    }),
+                )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NomSource;
+    use crate::NomBytes;
+    use ariadne::{Cache, Source};
+
+    #[test]
+    fn fetches_sources_by_id() {
+        let input = NomBytes::from("hello");
+        let mut cache = NomSource::new("a.txt", &input);
+
+        assert_eq!(cache.fetch(&"a.txt").unwrap().text(), "hello");
+        assert!(cache.fetch(&"b.txt").is_err());
+        assert_eq!(
+            cache.display(&"a.txt").map(|id| id.to_string()),
+            Some("a.txt".to_owned())
+        );
+    }
+
+    #[test]
+    fn shares_utf8_sources() {
+        let input = NomBytes::from("hello");
+        let cache = NomSource::new((), &input);
+        let source: &Source<_> = &cache.source;
+
+        assert_eq!(source.text().as_ptr(), input.as_bytes().as_ptr());
+    }
+
+    #[test]
+    fn decodes_invalid_utf8_lossily() {
+        let input = NomBytes::from(&b"caf\xe9!"[..]);
+        let mut cache = NomSource::new(0, &input);
+
+        assert_eq!(cache.fetch(&0).unwrap().text(), "caf\u{fffd}!");
+    }
+
+    #[test]
+    fn spans_slices() {
+        let input = NomBytes::from("key=value");
+        let cache = NomSource::new("kv", &input);
+
+        assert_eq!(cache.span(&input.slice_range(4..)), Some(("kv", 4..9)));
+        assert_eq!(cache.span(&input.slice_range(9..)), Some(("kv", 9..9)));
+        assert_eq!(cache.span(&NomBytes::from("value")), None);
+    }
+
+    #[cfg(feature = "nom7")]
+    mod reports {
+        use crate::{NomBytes, NomSource};
+        use ariadne::{Config, IndexType};
+        use core::ops::Range;
+        use nom::bytes::complete::tag;
+        use nom::bytes::streaming::tag as streaming_tag;
+        use nom::character::complete::{char, digit1};
+        use nom::combinator::map_res;
+        use nom::error::{context, VerboseError};
+        use nom::sequence::{delimited, preceded};
+        use nom::IResult;
+
+        fn render<E: crate::Labels>(
+            cache: &mut NomSource<&'static str>,
+            err: nom::Err<E>,
+        ) -> String {
+            let mut output = Vec::new();
+            cache
+                .build_report(err)
+                .with_config(
+                    Config::default()
+                        .with_index_type(IndexType::Byte)
+                        .with_color(false),
+                )
+                .finish()
+                .write(&mut *cache, &mut output)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        }
+
+        #[test]
+        fn reports_simple_errors() {
+            fn number(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+                preceded(tag("n="), digit1)(input)
+            }
+
+            let input = NomBytes::from("n=abc");
+            let mut cache = NomSource::new("n.txt", &input);
+            let output = render(&mut cache, number(input.clone()).unwrap_err());
+
+            assert!(output.contains("failed to parse input"), "{output}");
+            assert!(output.contains("n.txt:1:3"), "{output}");
+            assert!(output.contains("Digit"), "{output}");
+        }
+
+        #[test]
+        fn reports_verbose_error_contexts() {
+            fn list(input: NomBytes) -> IResult<NomBytes, NomBytes, VerboseError<NomBytes>> {
+                context("list", delimited(char('['), digit1, char(']')))(input)
+            }
+
+            // Multi-byte characters before the error must not shift the labels
+            let input = NomBytes::from("é = [12;");
+            let mut cache = NomSource::new("list.txt", &input);
+            let err = preceded(tag("é = "), list)(input.clone()).unwrap_err();
+            let output = render(&mut cache, err);
+
+            assert!(output.contains("list.txt:1:8"), "{output}");
+            assert!(output.contains("expected ']'"), "{output}");
+            assert!(output.contains("while parsing list"), "{output}");
+        }
+
+        #[test]
+        fn reports_nombytes_errors() {
+            fn port(input: NomBytes) -> IResult<NomBytes, u16, crate::error::Error> {
+                context(
+                    "port",
+                    map_res(digit1, |digits: NomBytes| digits.to_str().parse::<u16>()),
+                )(input)
+            }
+
+            let input = NomBytes::from("port 99999");
+            let mut cache = NomSource::new("port.txt", &input);
+            let err = preceded(tag("port "), port)(input.clone()).unwrap_err();
+            let output = render(&mut cache, err);
+
+            assert!(
+                output.contains("number too large to fit in target type"),
+                "{output}"
+            );
+            assert!(output.contains("while parsing port"), "{output}");
+        }
+
+        #[test]
+        fn reports_incomplete_input() {
+            let input = NomBytes::from("hel");
+            let mut cache = NomSource::new("hello.txt", &input);
+            let err = streaming_tag::<_, _, nom::error::Error<NomBytes>>("hello")(input.clone())
+                .unwrap_err();
+            let output = render(&mut cache, err);
+
+            assert!(output.contains("unexpected end of input"), "{output}");
+            assert!(output.contains("expected 2 more bytes"), "{output}");
+        }
+
+        #[test]
+        fn reports_custom_errors() {
+            struct Unexpected(NomBytes);
+
+            impl crate::Labels for Unexpected {
+                fn labels(&self, source: &NomBytes) -> Vec<(Range<usize>, String)> {
+                    let start = source.try_offset(&self.0).unwrap();
+                    vec![(start..start + self.0.len(), "unexpected word".to_owned())]
+                }
+            }
+
+            let input = NomBytes::from("hello world");
+            let mut cache = NomSource::new("hello.txt", &input);
+            let err = nom::Err::Error(Unexpected(input.slice_range(6..)));
+            let output = render(&mut cache, err);
+
+            assert!(output.contains("hello.txt:1:7"), "{output}");
+            assert!(output.contains("unexpected word"), "{output}");
+        }
+    }
+}
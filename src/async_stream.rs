@@ -0,0 +1,390 @@
+use crate::stream::{needed_len, reclaim};
+use crate::{NomBytes, StreamParserError};
+use bytes::Bytes;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_core::{ready, Stream};
+use nom::IResult;
+use pin_project_lite::pin_project;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+pin_project! {
+    /// A [`Stream`] that repeatedly runs a streaming `nom` parser over chunks
+    /// of data from another stream, yielding the parsed items.
+    ///
+    /// This is the asynchronous counterpart of [`StreamParser`]. The chunks
+    /// come from a [`Stream`] of [`Bytes`], a [`Stream`] of `Result<Bytes, E>`
+    /// if getting them can fail (see [`StreamChunk`]), or an [`AsyncRead`]
+    /// through [`AsyncStreamParser::from_reader`]. Whenever the
+    /// parser returns [`nom::Err::Incomplete`], more chunks are fetched until
+    /// at least as many bytes as the parser said it needed are buffered, and
+    /// the parser is run again.
+    ///
+    /// Since the data usually comes from somewhere we don't control, the
+    /// number of bytes the parser may need buffered for a single item can be
+    /// capped with [`with_max_buffer_size`](Self::with_max_buffer_size).
+    ///
+    /// The stream ends when the underlying stream does, at an item boundary.
+    /// If it ends in the middle of an item, or reading or parsing fails, the
+    /// error is yielded and the stream ends after that.
+    ///
+    /// [`StreamParser`]: crate::StreamParser
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use futures::StreamExt;
+    /// use nom::multi::length_data;
+    /// use nom::number::streaming::be_u8;
+    /// use nom::IResult;
+    /// use nombytes::{AsyncStreamParser, NomBytes};
+    ///
+    /// fn frame(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+    ///     length_data(be_u8)(input)
+    /// }
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let chunks = futures::stream::iter([
+    ///     Bytes::from_static(b"\x05hel"),
+    ///     Bytes::from_static(b"lo\x05world"),
+    /// ]);
+    /// let frames: Vec<_> = AsyncStreamParser::new(chunks, frame)
+    ///     .map(|frame| frame.unwrap().to_str().to_owned())
+    ///     .collect()
+    ///     .await;
+    ///
+    /// assert_eq!(frames, ["hello", "world"]);
+    /// # });
+    /// ```
+    #[derive(Debug)]
+    pub struct AsyncStreamParser<S, P> {
+        #[pin]
+        stream: S,
+        parser: P,
+        // The bytes that haven't been parsed yet, see `StreamParser`.
+        buf: Vec<u8>,
+        remaining: Bytes,
+        position: usize,
+        needed: usize,
+        max_buffer_size: usize,
+        done: bool,
+    }
+}
+
+impl<S, P> AsyncStreamParser<S, P> {
+    /// Creates a new `AsyncStreamParser` that parses items from the chunks
+    /// of `stream` using `parser`.
+    #[inline]
+    pub fn new(stream: S, parser: P) -> Self {
+        Self {
+            stream,
+            parser,
+            buf: Vec::new(),
+            remaining: Bytes::new(),
+            position: 0,
+            needed: 0,
+            max_buffer_size: usize::MAX,
+            done: false,
+        }
+    }
+
+    /// Sets the largest number of bytes the parser may need buffered to parse
+    /// a single item. If it asks for more than that, the stream yields
+    /// [`StreamParserError::BufferLimitExceeded`] instead of reading on.
+    /// Defaults to no limit.
+    ///
+    /// The buffer can still grow past the limit by up to one chunk, since
+    /// chunks are buffered whole.
+    #[must_use]
+    pub fn with_max_buffer_size(mut self, max_buffer_size: usize) -> Self {
+        self.max_buffer_size = max_buffer_size;
+        self
+    }
+
+    /// Returns how many bytes of the stream have been consumed by parsed
+    /// items so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns a reference to the underlying stream.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns the underlying stream, discarding any buffered bytes that
+    /// haven't been parsed yet.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<R: AsyncRead, P> AsyncStreamParser<ReaderStream<R>, P> {
+    /// Creates a new `AsyncStreamParser` that parses items from `reader`
+    /// using `parser`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::StreamExt;
+    /// use nom::bytes::streaming::{tag, take_until};
+    /// use nom::sequence::terminated;
+    /// use nom::IResult;
+    /// use nombytes::{AsyncStreamParser, NomBytes};
+    ///
+    /// fn line(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+    ///     terminated(take_until("\n"), tag("\n"))(input)
+    /// }
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut lines = AsyncStreamParser::from_reader(&b"first\nsecond\n"[..], line);
+    ///
+    /// assert_eq!(lines.next().await.unwrap().unwrap().to_str(), "first");
+    /// assert_eq!(lines.next().await.unwrap().unwrap().to_str(), "second");
+    /// assert!(lines.next().await.is_none());
+    /// # });
+    /// ```
+    #[inline]
+    pub fn from_reader(reader: R, parser: P) -> Self {
+        Self::new(ReaderStream::new(reader), parser)
+    }
+}
+
+/// An item of a [`Stream`] an [`AsyncStreamParser`] can get its chunks from.
+///
+/// This is [`Bytes`] for streams that can't fail, and `Result<Bytes, E>` for
+/// streams that can, like the one [`AsyncStreamParser::from_reader`] reads
+/// from. Errors end the parser's stream with [`StreamParserError::Io`].
+pub trait StreamChunk {
+    /// Returns the bytes of the chunk, or the error getting it failed with.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if getting the chunk failed.
+    fn into_chunk(self) -> std::io::Result<Bytes>;
+}
+
+impl StreamChunk for Bytes {
+    #[inline]
+    fn into_chunk(self) -> std::io::Result<Bytes> {
+        Ok(self)
+    }
+}
+
+impl<E: Into<std::io::Error>> StreamChunk for Result<Bytes, E> {
+    #[inline]
+    fn into_chunk(self) -> std::io::Result<Bytes> {
+        self.map_err(Into::into)
+    }
+}
+
+impl<S, P, O, E> Stream for AsyncStreamParser<S, P>
+where
+    S: Stream,
+    S::Item: StreamChunk,
+    P: FnMut(NomBytes) -> IResult<NomBytes, O, E>,
+{
+    type Item = Result<O, StreamParserError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let buffered = this.remaining.len() + this.buf.len();
+            if buffered == 0 || buffered < *this.needed {
+                match ready!(this.stream.as_mut().poll_next(cx)).map(StreamChunk::into_chunk) {
+                    Some(Ok(chunk)) => {
+                        if buffered == 0 {
+                            // Nothing to put it after, so there's no need to
+                            // copy it.
+                            *this.remaining = chunk;
+                        } else {
+                            if !this.remaining.is_empty() {
+                                *this.buf = reclaim(core::mem::take(this.remaining));
+                            }
+                            this.buf.extend_from_slice(&chunk);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        *this.done = true;
+                        return Poll::Ready(Some(Err(StreamParserError::Io(e))));
+                    }
+                    None => {
+                        *this.done = true;
+                        return Poll::Ready(match buffered {
+                            0 => None,
+                            len => Some(Err(StreamParserError::Incomplete {
+                                offset: *this.position,
+                                len,
+                            })),
+                        });
+                    }
+                }
+                continue;
+            }
+
+            if !this.buf.is_empty() {
+                *this.remaining = Bytes::from(core::mem::take(this.buf));
+            }
+
+            let input = NomBytes::new(core::mem::take(this.remaining));
+            *this.needed = 0;
+            match (this.parser)(input.clone()) {
+                Ok((rest, item)) => {
+                    *this.position += input.offset_to(&rest);
+                    *this.remaining = rest.into_bytes();
+                    return Poll::Ready(Some(Ok(item)));
+                }
+                Err(nom::Err::Incomplete(needed)) => {
+                    *this.needed = needed_len(input.as_bytes().len(), needed);
+                    if *this.needed > *this.max_buffer_size {
+                        *this.done = true;
+                        return Poll::Ready(Some(Err(StreamParserError::BufferLimitExceeded {
+                            offset: *this.position,
+                            needed: *this.needed,
+                            limit: *this.max_buffer_size,
+                        })));
+                    }
+                    *this.buf = reclaim(input.into_bytes());
+                }
+                Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(StreamParserError::Parse {
+                        offset: *this.position,
+                        error,
+                    })));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncStreamParser;
+    use crate::{NomBytes, StreamParserError};
+    use bytes::Bytes;
+    use futures::StreamExt;
+    use nom::bytes::streaming::{tag, take_until};
+    use nom::error::ErrorKind;
+    use nom::multi::length_data;
+    use nom::number::streaming::be_u16;
+    use nom::sequence::terminated;
+    use nom::IResult;
+    use tokio::io::AsyncWriteExt;
+
+    fn line(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+        terminated(take_until("\n"), tag("\n"))(input)
+    }
+
+    fn frame(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+        length_data(be_u16)(input)
+    }
+
+    fn chunks(chunks: &'static [&'static [u8]]) -> impl futures::Stream<Item = Bytes> {
+        futures::stream::iter(chunks.iter().map(|c| Bytes::from_static(c)))
+    }
+
+    #[tokio::test]
+    async fn parses_items_from_a_reader() {
+        let (mut writer, reader) = tokio::io::duplex(4);
+        let frames = AsyncStreamParser::from_reader(reader, frame);
+
+        tokio::spawn(async move {
+            writer
+                .write_all(b"\x00\x0bhello world\x00\x00\x00\x03end")
+                .await
+                .unwrap();
+        });
+
+        let decoded: Vec<_> = frames
+            .map(|frame| frame.unwrap().to_str().to_owned())
+            .collect()
+            .await;
+        assert_eq!(decoded, ["hello world", "", "end"]);
+    }
+
+    #[tokio::test]
+    async fn parses_items_across_chunks() {
+        let stream = chunks(&[b"al", b"pha\nbe", b"ta\n\n", b"gamma", b"\n"]);
+        let lines: Vec<_> = AsyncStreamParser::new(stream, line)
+            .map(|l| l.unwrap().to_str().to_owned())
+            .collect()
+            .await;
+
+        assert_eq!(lines, ["alpha", "beta", "", "gamma"]);
+    }
+
+    #[tokio::test]
+    async fn stops_when_the_parser_needs_too_much() {
+        let stream = chunks(&[b"\x00\x02ok\xff", b"\xffxxxx", b"xxxx"]);
+        let mut items = AsyncStreamParser::new(stream, frame).with_max_buffer_size(16);
+
+        assert_eq!(items.next().await.unwrap().unwrap().to_str(), "ok");
+        assert!(matches!(
+            items.next().await,
+            Some(Err(StreamParserError::BufferLimitExceeded {
+                offset: 4,
+                needed: 0x1_0001,
+                limit: 16,
+            }))
+        ));
+        assert!(items.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_incomplete_item_at_eof() {
+        let mut lines = AsyncStreamParser::new(chunks(&[b"one\n", b"tw", b"o"]), line);
+
+        assert_eq!(lines.next().await.unwrap().unwrap().to_str(), "one");
+        assert!(matches!(
+            lines.next().await,
+            Some(Err(StreamParserError::Incomplete { offset: 4, len: 3 }))
+        ));
+        assert!(lines.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_errors_from_the_stream() {
+        let stream = futures::stream::iter([
+            Ok(Bytes::from_static(b"one\n")),
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "oops")),
+        ]);
+        let mut lines = AsyncStreamParser::new(stream, line);
+
+        assert_eq!(lines.next().await.unwrap().unwrap().to_str(), "one");
+        assert!(matches!(
+            lines.next().await,
+            Some(Err(StreamParserError::Io(_)))
+        ));
+        assert!(lines.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_parse_errors_with_stream_offset() {
+        fn greeting(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+            terminated(tag("hi"), tag("\n"))(input)
+        }
+
+        let mut items = AsyncStreamParser::new(chunks(&[b"hi\nh", b"i\nho\n"]), greeting);
+
+        assert!(items.next().await.unwrap().is_ok());
+        assert!(items.next().await.unwrap().is_ok());
+        match items.next().await {
+            Some(Err(StreamParserError::Parse { offset, error })) => {
+                assert_eq!(offset, 6);
+                assert_eq!(error.code, ErrorKind::Tag);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(items.position(), 6);
+    }
+}
@@ -0,0 +1,176 @@
+use alloc::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use bytes::Bytes;
+use core::ops::Range;
+
+/// Storage a [`GenericNomBytes`](crate::GenericNomBytes) can be backed by.
+///
+/// A `GenericNomBytes` always holds on to all of its backing, its *origin*,
+/// and only narrows the range of it it covers when it's sliced. So slicing is
+/// as cheap as cloning the backing, and an empty slice keeps its place in its
+/// origin, whatever the backing does with empty slices of its own.
+///
+/// This is implemented for [`Bytes`], `Arc<[u8]>`, `Rc<[u8]>`, which avoids
+/// atomics in single-threaded code, and `&'static [u8]`, which doesn't
+/// allocate at all.
+///
+/// # Implementing `Backing`
+///
+/// Cloning should be cheap, since parsers clone their input a lot, and
+/// [`as_slice`](Self::as_slice) must return the very same memory, at the
+/// same address, for a value and all of its clones. Offsets between two
+/// slices of the same input are worked out from their addresses.
+pub trait Backing: Clone {
+    /// Returns all the bytes this holds.
+    fn as_slice(&self) -> &[u8];
+
+    /// Returns a new backing holding only `range` of this one, on its own.
+    ///
+    /// This is only used to hand out the contents of a `GenericNomBytes`
+    /// through [`into_backing`](crate::GenericNomBytes::into_backing) and
+    /// [`to_backing`](crate::GenericNomBytes::to_backing), so it's fine for
+    /// it to copy, and for an empty result to be unrelated to `self`.
+    ///
+    /// # Panics
+    ///
+    /// May panic if `range` is out of bounds.
+    #[must_use]
+    fn slice(&self, range: Range<usize>) -> Self;
+
+    /// Returns `range` of this backing as [`Bytes`].
+    ///
+    /// This copies by default. `Bytes` and `&'static [u8]` hand out the
+    /// same memory instead.
+    ///
+    /// # Panics
+    ///
+    /// May panic if `range` is out of bounds.
+    #[must_use]
+    fn slice_to_bytes(&self, range: Range<usize>) -> Bytes {
+        Bytes::copy_from_slice(&self.as_slice()[range])
+    }
+}
+
+impl Backing for Bytes {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn slice(&self, range: Range<usize>) -> Self {
+        Bytes::slice(self, range)
+    }
+
+    #[inline]
+    fn slice_to_bytes(&self, range: Range<usize>) -> Bytes {
+        Bytes::slice(self, range)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Backing for Arc<[u8]> {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn slice(&self, range: Range<usize>) -> Self {
+        if range == (0..self.len()) {
+            self.clone()
+        } else {
+            Arc::from(&self[range])
+        }
+    }
+}
+
+impl Backing for Rc<[u8]> {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn slice(&self, range: Range<usize>) -> Self {
+        if range == (0..self.len()) {
+            self.clone()
+        } else {
+            Rc::from(&self[range])
+        }
+    }
+}
+
+impl Backing for &'static [u8] {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn slice(&self, range: Range<usize>) -> Self {
+        &self[range]
+    }
+
+    #[inline]
+    fn slice_to_bytes(&self, range: Range<usize>) -> Bytes {
+        Bytes::from_static(&self[range])
+    }
+}
+
+#[cfg(all(test, feature = "nom7"))]
+mod tests {
+    use super::Backing;
+    use crate::GenericNomBytes;
+    use alloc::rc::Rc;
+    use alloc::sync::Arc;
+    use nom::bytes::complete::{tag, take_until};
+    use nom::sequence::preceded;
+    use nom::{IResult, Offset};
+
+    fn value<B: Backing>(
+        input: GenericNomBytes<B>,
+    ) -> IResult<GenericNomBytes<B>, GenericNomBytes<B>> {
+        preceded(tag("key="), take_until(";"))(input)
+    }
+
+    fn parses_with<B: Backing>(backing: B) {
+        let input = GenericNomBytes::new(backing);
+        let (rest, value) = value(input.clone()).unwrap();
+
+        assert_eq!(value.as_bytes(), b"value");
+        assert_eq!(value.span(), 4..9);
+        assert!(value.same_origin(&input));
+        assert_eq!(value.to_backing().as_slice(), b"value");
+        assert_eq!(value.into_backing().as_slice(), b"value");
+
+        // Empty slices keep their place in the origin
+        let (end, _) = tag::<_, _, ()>(";")(rest).unwrap();
+        assert_eq!(end.as_bytes(), b"");
+        assert_eq!(input.offset(&end), 10);
+        assert_eq!(input.try_offset(&end), Some(10));
+    }
+
+    #[test]
+    fn parses_with_every_backing() {
+        static INPUT: &[u8] = b"key=value;";
+
+        parses_with(bytes::Bytes::from_static(INPUT));
+        parses_with(Arc::<[u8]>::from(INPUT));
+        parses_with(Rc::<[u8]>::from(INPUT));
+        parses_with(INPUT);
+    }
+
+    #[test]
+    fn hands_out_the_origin_when_it_can() {
+        let rc = Rc::<[u8]>::from(&b"whole"[..]);
+        let input = GenericNomBytes::new(rc.clone());
+
+        assert!(Rc::ptr_eq(&input.to_backing(), &rc));
+        assert!(Rc::ptr_eq(&input.clone().into_backing(), &rc));
+        // Only part of it has to be copied out
+        let part = input.slice_range(1..).into_backing();
+        assert!(!Rc::ptr_eq(&part, &rc));
+        assert_eq!(&*part, b"hole");
+        assert_eq!(alloc::format!("{input:?}"), r#"NomBytes(b"whole", 0..5)"#);
+    }
+}
@@ -0,0 +1,521 @@
+//! Bit-level parsing of [`NomBytes`](crate::NomBytes), the counterpart of
+//! `nom::bits`.
+//!
+//! `nom::bits` parsers run on an `(input, bit offset)` tuple. [`NomBits`] is
+//! that pair as a type of its own: a [`GenericNomBytes`] plus the number of
+//! bits of its first byte that have already been read. [`bits`] switches a
+//! byte-level parser over to it and [`bytes`](fn@bytes) switches back, and the
+//! [`complete`] and [`streaming`] modules have the parsers that take bit
+//! fields. Since `NomBits` still holds on to the bytes it was created from,
+//! byte-aligned parts of a bit stream come back out of [`bytes`](fn@bytes) as
+//! zero-copy slices of the original input.
+//!
+//! # Examples
+//!
+//! ```
+//! use nom::bytes::complete::take;
+//! use nom::error::Error;
+//! use nom::sequence::tuple;
+//! use nom::IResult;
+//! use nombytes::bits::{bits, bytes, complete};
+//! use nombytes::{NomBits, NomBytes};
+//!
+//! // A 4 bit version, a 4 bit length, then that many bytes of payload
+//! fn packet(input: NomBytes) -> IResult<NomBytes, (u8, NomBytes)> {
+//!     let (input, (version, len)) = bits::<_, _, Error<NomBits>, _, _>(tuple((
+//!         complete::take(4usize),
+//!         complete::take::<_, usize, _, _>(4usize),
+//!     )))(input)?;
+//!     let (input, payload) = take(len)(input)?;
+//!     Ok((input, (version, payload)))
+//! }
+//!
+//! fn header(input: NomBytes) -> IResult<NomBytes, (bool, NomBytes)> {
+//!     bits::<_, _, Error<NomBits>, _, _>(tuple((
+//!         complete::bool,
+//!         bytes::<_, _, Error<NomBytes>, _, _>(take(3usize)),
+//!     )))(input)
+//! }
+//!
+//! let input = NomBytes::from(&b"\x13abc"[..]);
+//! let (_, (version, payload)) = packet(input.clone()).unwrap();
+//! assert_eq!((version, payload.to_str()), (1, "abc"));
+//! // The payload wasn't copied out of the input
+//! assert_eq!(payload.span(), 1..4);
+//!
+//! let (_, (flag, rest)) = header(input).unwrap();
+//! assert_eq!((flag, rest.to_str()), (false, "abc"));
+//! ```
+
+use crate::{Backing, GenericNomBytes};
+use bytes::Bytes;
+use core::ops::{AddAssign, Shl, Shr};
+use nom::error::{ErrorKind, ParseError};
+use nom::{IResult, InputLength, Needed, Parser};
+
+/// A [`GenericNomBytes`] read bit by bit.
+///
+/// Along with the bytes, this keeps the number of bits of the first byte
+/// that have already been read, which is always less than 8. Bits are read
+/// most significant first, like `nom::bits` does.
+///
+/// # Examples
+///
+/// ```
+/// use nom::error::Error;
+/// use nombytes::bits::complete::take;
+/// use nombytes::{NomBits, NomBytes};
+///
+/// let input = NomBits::new(NomBytes::from(&b"\xa5\xff"[..]));
+/// let (rest, nibble) = take::<_, u8, _, Error<_>>(4usize)(input).unwrap();
+///
+/// assert_eq!(nibble, 0xa);
+/// assert_eq!(rest.bit_offset(), 4);
+/// assert_eq!(rest.bit_len(), 12);
+/// ```
+#[derive(Clone)]
+pub struct NomBits<B = Bytes> {
+    bytes: GenericNomBytes<B>,
+    bit_offset: usize,
+}
+
+impl<B: Backing> NomBits<B> {
+    /// Creates a new `NomBits` starting at the first bit of `bytes`.
+    #[inline]
+    pub fn new(bytes: GenericNomBytes<B>) -> Self {
+        Self {
+            bytes,
+            bit_offset: 0,
+        }
+    }
+
+    /// Creates a new `NomBits` starting `bit_offset` bits into `bytes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_offset` is past the end of `bytes`.
+    #[must_use]
+    pub fn with_bit_offset(bytes: GenericNomBytes<B>, bit_offset: usize) -> Self {
+        let len = bytes.as_bytes().len();
+        assert!(
+            bit_offset <= len * 8,
+            "bit offset {bit_offset} is out of bounds for {len} bytes"
+        );
+        let bytes = match bit_offset / 8 {
+            0 => bytes,
+            skipped => bytes.slice_range(skipped..),
+        };
+        Self {
+            bytes,
+            bit_offset: bit_offset % 8,
+        }
+    }
+
+    /// Returns the bytes the remaining bits are in, including the partially
+    /// read first byte, if any, and the bit offset into it.
+    #[inline]
+    #[must_use]
+    pub fn into_parts(self) -> (GenericNomBytes<B>, usize) {
+        (self.bytes, self.bit_offset)
+    }
+
+    /// Returns the bytes the remaining bits are in, including the partially
+    /// read first byte, if any.
+    #[inline]
+    #[must_use]
+    pub fn as_nom_bytes(&self) -> &GenericNomBytes<B> {
+        &self.bytes
+    }
+
+    /// Returns the number of bits of the first byte that have already been
+    /// read.
+    #[inline]
+    #[must_use]
+    pub fn bit_offset(&self) -> usize {
+        self.bit_offset
+    }
+
+    /// Returns the number of bits left.
+    #[inline]
+    #[must_use]
+    pub fn bit_len(&self) -> usize {
+        self.bytes.as_bytes().len() * 8 - self.bit_offset
+    }
+
+    /// Returns `true` if there are no bits left.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.as_bytes().is_empty()
+    }
+
+    /// Returns `true` if this starts at a byte boundary.
+    #[inline]
+    #[must_use]
+    pub fn is_aligned(&self) -> bool {
+        self.bit_offset == 0
+    }
+
+    /// Returns the bytes from the next byte boundary on, skipping the rest
+    /// of a partially read byte. This shares memory with the input, like
+    /// slicing a `NomBytes` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::{NomBits, NomBytes};
+    ///
+    /// let input = NomBytes::from("abc");
+    /// assert_eq!(NomBits::with_bit_offset(input.clone(), 8).aligned().to_str(), "bc");
+    /// assert_eq!(NomBits::with_bit_offset(input, 9).aligned().to_str(), "c");
+    /// ```
+    #[must_use]
+    pub fn aligned(&self) -> GenericNomBytes<B> {
+        if self.is_aligned() {
+            self.bytes.clone()
+        } else {
+            self.bytes.slice_range(1..)
+        }
+    }
+
+    /// Splits off the next `count` bits as a number, or returns `None` if
+    /// there aren't that many bits left. This is the implementation behind
+    /// [`complete::take`] and [`streaming::take`].
+    fn split_bits<O>(&self, count: usize) -> Option<(Self, O)>
+    where
+        O: From<u8> + AddAssign + Shl<usize, Output = O> + Shr<usize, Output = O>,
+    {
+        if count > self.bit_len() {
+            return None;
+        }
+
+        let mut acc: O = 0_u8.into();
+        let mut offset = self.bit_offset;
+        let mut remaining = count;
+        for &byte in self.bytes.as_bytes() {
+            if remaining == 0 {
+                break;
+            }
+            let available = 8 - offset;
+            let val: O = (byte & (0xff >> offset)).into();
+            if remaining < available {
+                acc += val >> (available - remaining);
+                break;
+            }
+            acc += val << (remaining - available);
+            remaining -= available;
+            offset = 0;
+        }
+
+        let rest = Self::with_bit_offset(self.bytes.clone(), self.bit_offset + count);
+        Some((rest, acc))
+    }
+}
+
+impl<B: Backing> From<GenericNomBytes<B>> for NomBits<B> {
+    #[inline]
+    fn from(bytes: GenericNomBytes<B>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl<B: Backing> InputLength for NomBits<B> {
+    /// The number of bits left, so that `nom` sees progress when a bit is
+    /// read.
+    #[inline]
+    fn input_len(&self) -> usize {
+        self.bit_len()
+    }
+}
+
+impl<B: Backing> core::fmt::Debug for NomBits<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("NomBits")
+            .field(&self.bytes)
+            .field(&self.bit_offset)
+            .finish()
+    }
+}
+
+// Equal if the same bits are left at the same bit offset, regardless of the
+// bits of the first byte that were already read.
+impl<B: Backing> PartialEq for NomBits<B> {
+    fn eq(&self, other: &Self) -> bool {
+        let mask = 0xff >> self.bit_offset;
+        self.bit_offset == other.bit_offset
+            && match (
+                self.bytes.as_bytes().split_first(),
+                other.bytes.as_bytes().split_first(),
+            ) {
+                (Some((a, a_rest)), Some((b, b_rest))) => a & mask == b & mask && a_rest == b_rest,
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+impl<B: Backing> Eq for NomBits<B> {}
+
+/// Converts errors between bit-level and byte-level parsers, for [`bits`]
+/// and [`bytes`](fn@bytes).
+///
+/// This is the equivalent of `nom::ErrorConvert`, which can't be
+/// implemented for `nom`'s own error types over [`NomBits`] outside of
+/// `nom`. Bit-level errors point at the byte they happened in.
+pub trait ErrorConvert<E> {
+    /// Converts this error into `E`.
+    fn convert(self) -> E;
+}
+
+impl<B: Backing> ErrorConvert<nom::error::Error<GenericNomBytes<B>>>
+    for nom::error::Error<NomBits<B>>
+{
+    #[inline]
+    fn convert(self) -> nom::error::Error<GenericNomBytes<B>> {
+        nom::error::Error::new(self.input.bytes, self.code)
+    }
+}
+
+impl<B: Backing> ErrorConvert<nom::error::Error<NomBits<B>>>
+    for nom::error::Error<GenericNomBytes<B>>
+{
+    #[inline]
+    fn convert(self) -> nom::error::Error<NomBits<B>> {
+        nom::error::Error::new(NomBits::new(self.input), self.code)
+    }
+}
+
+impl<B: Backing> ErrorConvert<(GenericNomBytes<B>, ErrorKind)> for (NomBits<B>, ErrorKind) {
+    #[inline]
+    fn convert(self) -> (GenericNomBytes<B>, ErrorKind) {
+        (self.0.bytes, self.1)
+    }
+}
+
+impl<B: Backing> ErrorConvert<(NomBits<B>, ErrorKind)> for (GenericNomBytes<B>, ErrorKind) {
+    #[inline]
+    fn convert(self) -> (NomBits<B>, ErrorKind) {
+        (NomBits::new(self.0), self.1)
+    }
+}
+
+impl ErrorConvert<()> for () {
+    #[inline]
+    fn convert(self) {}
+}
+
+/// Runs a bit-level parser on a byte-level input, like `nom::bits::bits`.
+///
+/// Afterwards, the rest of a partially read byte is skipped, and parsing
+/// carries on at the next byte.
+pub fn bits<B, O, E1, E2, P>(
+    mut parser: P,
+) -> impl FnMut(GenericNomBytes<B>) -> IResult<GenericNomBytes<B>, O, E2>
+where
+    B: Backing,
+    E1: ParseError<NomBits<B>> + ErrorConvert<E2>,
+    E2: ParseError<GenericNomBytes<B>>,
+    P: Parser<NomBits<B>, O, E1>,
+{
+    move |input| match parser.parse(NomBits::new(input)) {
+        Ok((rest, result)) => Ok((rest.aligned(), result)),
+        Err(nom::Err::Incomplete(n)) => Err(nom::Err::Incomplete(n.map(|u| u.get() / 8 + 1))),
+        Err(nom::Err::Error(e)) => Err(nom::Err::Error(e.convert())),
+        Err(nom::Err::Failure(e)) => Err(nom::Err::Failure(e.convert())),
+    }
+}
+
+/// Runs a byte-level parser on a bit-level input, like `nom::bits::bytes`.
+///
+/// The rest of a partially read byte is skipped first, so `parser` starts
+/// at the next byte, and gets a zero-copy slice of the input from there.
+pub fn bytes<B, O, E1, E2, P>(mut parser: P) -> impl FnMut(NomBits<B>) -> IResult<NomBits<B>, O, E2>
+where
+    B: Backing,
+    E1: ParseError<GenericNomBytes<B>> + ErrorConvert<E2>,
+    E2: ParseError<NomBits<B>>,
+    P: Parser<GenericNomBytes<B>, O, E1>,
+{
+    move |input| match parser.parse(input.aligned()) {
+        Ok((rest, result)) => Ok((NomBits::new(rest), result)),
+        Err(nom::Err::Incomplete(Needed::Unknown)) => Err(nom::Err::Incomplete(Needed::Unknown)),
+        Err(nom::Err::Incomplete(Needed::Size(sz))) => Err(match sz.get().checked_mul(8) {
+            Some(v) => nom::Err::Incomplete(Needed::new(v)),
+            None => nom::Err::Failure(E2::from_error_kind(input, ErrorKind::TooLarge)),
+        }),
+        Err(nom::Err::Error(e)) => Err(nom::Err::Error(e.convert())),
+        Err(nom::Err::Failure(e)) => Err(nom::Err::Failure(e.convert())),
+    }
+}
+
+macro_rules! bit_parsers {
+    ($not_enough:expr) => {
+        use super::NomBits;
+        use crate::Backing;
+        use core::ops::{AddAssign, Shl, Shr};
+        use nom::error::{ErrorKind, ParseError};
+        use nom::{IResult, ToUsize};
+
+        /// Generates a parser taking `count` bits as a number, most
+        /// significant bit first.
+        ///
+        /// `O` has to be wide enough for `count` bits.
+        pub fn take<B, O, C, E>(count: C) -> impl Fn(NomBits<B>) -> IResult<NomBits<B>, O, E>
+        where
+            B: Backing,
+            O: From<u8> + AddAssign + Shl<usize, Output = O> + Shr<usize, Output = O>,
+            C: ToUsize,
+            E: ParseError<NomBits<B>>,
+        {
+            let count = count.to_usize();
+            move |input: NomBits<B>| match input.split_bits(count) {
+                Some(result) => Ok(result),
+                None => {
+                    let not_enough: fn(NomBits<B>, usize) -> nom::Err<E> = $not_enough;
+                    Err(not_enough(input, count))
+                }
+            }
+        }
+
+        /// Generates a parser taking `count` bits and comparing them to
+        /// `pattern`.
+        pub fn tag<B, O, C, E>(
+            pattern: O,
+            count: C,
+        ) -> impl Fn(NomBits<B>) -> IResult<NomBits<B>, O, E>
+        where
+            B: Backing,
+            O: From<u8> + AddAssign + Shl<usize, Output = O> + Shr<usize, Output = O> + PartialEq,
+            C: ToUsize,
+            E: ParseError<NomBits<B>>,
+        {
+            let count = count.to_usize();
+            move |input: NomBits<B>| {
+                let (rest, o) = take(count)(input.clone())?;
+                if o == pattern {
+                    Ok((rest, o))
+                } else {
+                    Err(nom::Err::Error(E::from_error_kind(
+                        input,
+                        ErrorKind::TagBits,
+                    )))
+                }
+            }
+        }
+
+        /// Parses one bit as a `bool`.
+        ///
+        /// # Errors
+        ///
+        /// Fails like [`take`] does if there are no bits left.
+        pub fn bool<B, E>(input: NomBits<B>) -> IResult<NomBits<B>, bool, E>
+        where
+            B: Backing,
+            E: ParseError<NomBits<B>>,
+        {
+            let (rest, bit): (_, u8) = take(1usize)(input)?;
+            Ok((rest, bit != 0))
+        }
+    };
+}
+
+/// Bit-level parsers that fail with `ErrorKind::Eof` at the end of the
+/// input.
+pub mod complete {
+    bit_parsers!(|input, _| nom::Err::Error(E::from_error_kind(input, ErrorKind::Eof)));
+}
+
+/// Bit-level parsers that ask for more input at the end of the input.
+pub mod streaming {
+    bit_parsers!(|input, count| nom::Err::Incomplete(nom::Needed::new(count - input.bit_len())));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bits, bytes, complete, streaming, NomBits};
+    use crate::NomBytes;
+    use nom::bytes::complete::take;
+    use nom::error::{Error, ErrorKind};
+    use nom::multi::fold_many0;
+    use nom::sequence::tuple;
+    use nom::{IResult, Needed};
+
+    type BitsError = Error<NomBits>;
+
+    #[test]
+    fn takes_bit_fields_across_bytes() {
+        let input = NomBits::new(NomBytes::from(&b"\x12\x34\x56"[..]));
+        let (rest, (a, b, c)): (_, (u8, u16, u8)) = tuple((
+            complete::take::<_, _, _, BitsError>(4usize),
+            complete::take(12usize),
+            complete::take(3usize),
+        ))(input)
+        .unwrap();
+
+        assert_eq!((a, b, c), (0x1, 0x234, 0b010));
+        assert_eq!(rest.bit_offset(), 3);
+        assert_eq!(rest.bit_len(), 5);
+        assert_eq!(rest.as_nom_bytes().span(), 2..3);
+    }
+
+    #[test]
+    fn switches_between_bits_and_bytes() {
+        fn parser(input: NomBytes) -> IResult<NomBytes, (u8, NomBytes, bool)> {
+            bits::<_, _, BitsError, _, _>(tuple((
+                complete::take(3usize),
+                bytes::<_, _, Error<NomBytes>, _, _>(take(2usize)),
+                complete::bool,
+            )))(input)
+        }
+
+        let input = NomBytes::from(&b"\xe0ab\x80c"[..]);
+        let (rest, (n, ab, flag)) = parser(input.clone()).unwrap();
+
+        assert_eq!(n, 0b111);
+        assert_eq!(ab.to_str(), "ab");
+        assert_eq!(ab.as_bytes().as_ptr(), input.as_bytes()[1..].as_ptr());
+        assert!(flag);
+        assert_eq!(rest.to_str(), "c");
+    }
+
+    #[test]
+    fn reports_errors_and_missing_bits() {
+        let input = NomBits::with_bit_offset(NomBytes::from(&b"\x0f"[..]), 4);
+
+        assert_eq!(
+            complete::take::<_, u8, _, BitsError>(5usize)(input.clone()),
+            Err(nom::Err::Error(Error::new(input.clone(), ErrorKind::Eof)))
+        );
+        assert_eq!(
+            streaming::take::<_, u8, _, BitsError>(13usize)(input.clone()),
+            Err(nom::Err::Incomplete(Needed::new(9)))
+        );
+        assert_eq!(
+            complete::tag::<_, u8, _, BitsError>(0b1110, 4usize)(input.clone()),
+            Err(nom::Err::Error(Error::new(
+                input.clone(),
+                ErrorKind::TagBits
+            )))
+        );
+        let (rest, _) = complete::tag::<_, u8, _, BitsError>(0b1111, 4usize)(input).unwrap();
+        assert!(rest.is_empty());
+
+        let incomplete = bits::<_, u16, BitsError, Error<NomBytes>, _>(streaming::take(12usize))(
+            NomBytes::from(&b"\xff"[..]),
+        );
+        assert_eq!(incomplete, Err(nom::Err::Incomplete(Needed::new(1))));
+    }
+
+    #[test]
+    fn repeats_until_the_bits_run_out() {
+        let input = NomBits::new(NomBytes::from(&b"\xa5"[..]));
+        let (rest, ones) = fold_many0(
+            complete::bool::<_, BitsError>,
+            || 0,
+            |ones, bit| ones + usize::from(bit),
+        )(input)
+        .unwrap();
+
+        assert_eq!(ones, 4);
+        assert!(rest.is_empty());
+    }
+}
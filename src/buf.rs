@@ -0,0 +1,105 @@
+use crate::{Backing, GenericNomBytes};
+use bytes::{Buf, Bytes};
+
+/// Reading a [`GenericNomBytes`] as a [`Buf`] consumes it from the front,
+/// the same way a `nom` parser taking some of it would. It keeps its place
+/// in its origin while doing so, so the offsets, spans and line numbers of
+/// what's left stay right, and `nom` parsing can carry on where the `Buf`
+/// reads left off.
+///
+/// [`copy_to_bytes`](Buf::copy_to_bytes) doesn't copy anything when the
+/// backing is a [`Bytes`] or a `&'static [u8]`, see
+/// [`Backing::slice_to_bytes`].
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Buf;
+/// use nombytes::NomBytes;
+///
+/// let input = NomBytes::from(&b"\x05\x00\x00\x00hello, world"[..]);
+/// let mut rest = input.clone();
+/// let len = rest.get_u32_le() as usize;
+/// let hello = rest.copy_to_bytes(len);
+///
+/// assert_eq!(hello, "hello");
+/// assert_eq!(rest.span(), 9..16);
+/// assert_eq!(input.try_offset(&rest), Some(9));
+/// ```
+impl<B: Backing> Buf for GenericNomBytes<B> {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.as_bytes()
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+        let remaining = self.remaining();
+        assert!(
+            cnt <= remaining,
+            "cannot advance past `remaining`: {cnt} <= {remaining}"
+        );
+        self.start += cnt;
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        let remaining = self.remaining();
+        assert!(
+            len <= remaining,
+            "`len` greater than remaining: {len} <= {remaining}"
+        );
+        let bytes = self.origin.slice_to_bytes(self.start..self.start + len);
+        self.start += len;
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GenericNomBytes, NomBytes};
+    use alloc::rc::Rc;
+    use bytes::{Buf, Bytes};
+
+    #[test]
+    fn advances_within_the_origin() {
+        let input = NomBytes::from("abcdef");
+        let mut buf = input.clone();
+
+        buf.advance(2);
+        assert_eq!(buf.chunk(), b"cdef");
+        assert_eq!(buf.get_u8(), b'c');
+        buf.advance(3);
+
+        assert_eq!(buf.remaining(), 0);
+        assert!(!buf.has_remaining());
+        assert_eq!(buf.span(), 6..6);
+        assert!(buf.same_origin(&input));
+        assert_eq!(input.try_offset(&buf), Some(6));
+    }
+
+    #[test]
+    fn copies_to_bytes_without_copying_when_it_can() {
+        let bytes = Bytes::from_static(b"header:body");
+        let mut buf = NomBytes::new(bytes.clone());
+        let header = buf.copy_to_bytes(6);
+        assert_eq!(header, "header");
+        assert_eq!(header.as_ptr(), bytes.as_ptr());
+        assert_eq!(buf.as_bytes(), b":body");
+
+        let mut buf = GenericNomBytes::new(Rc::<[u8]>::from(&b"header:body"[..]));
+        buf.advance(7);
+        assert_eq!(buf.copy_to_bytes(4), "body");
+        assert_eq!(buf.span(), 11..11);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot advance past `remaining`")]
+    fn advancing_past_the_end_panics() {
+        NomBytes::from("abc").slice_range(1..).advance(3);
+    }
+}
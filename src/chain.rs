@@ -0,0 +1,837 @@
+use crate::NomBytes;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use bytes::{Bytes, BytesMut};
+use core::ops::{Bound, RangeBounds};
+
+/// An input made up of several [`Bytes`] segments that parses as if they had
+/// been concatenated, without concatenating them.
+///
+/// Data read from the network usually arrives as a sequence of `Bytes`
+/// chunks, and an item can start in one chunk and end in another. Rather
+/// than copying all of them into one buffer to get a [`NomBytes`], they can
+/// be collected into a `NomChain` and parsed as they are.
+///
+/// Slicing a `NomChain`, which is what `nom` does whenever it takes some of
+/// the input, never copies anything; the slices share the list of segments
+/// and only track which part of it they cover. Offsets are counted in the
+/// logical, concatenated input. To turn a parsed slice into a [`NomBytes`],
+/// use [`to_nom_bytes`](Self::to_nom_bytes), which only copies if the slice
+/// straddles a segment boundary.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "nom7")]
+/// # {
+/// use bytes::Bytes;
+/// use nom::bytes::complete::{tag, take_until};
+/// use nom::IResult;
+/// use nombytes::NomChain;
+///
+/// fn header(input: NomChain) -> IResult<NomChain, NomChain> {
+///     let (input, _) = tag("Host: ")(input)?;
+///     let (input, host) = take_until("\r\n")(input)?;
+///     let (input, _) = tag("\r\n")(input)?;
+///     Ok((input, host))
+/// }
+///
+/// let input = NomChain::from_iter([
+///     Bytes::from_static(b"Host: exa"),
+///     Bytes::from_static(b"mple.com\r"),
+///     Bytes::from_static(b"\n"),
+/// ]);
+/// let (rest, host) = header(input).unwrap();
+///
+/// assert_eq!(host.to_nom_bytes().to_str(), "example.com");
+/// assert!(rest.is_empty());
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NomChain {
+    segments: Arc<[Segment]>,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Segment {
+    // Where in the concatenated input this segment starts.
+    start: usize,
+    bytes: Bytes,
+}
+
+impl NomChain {
+    /// Creates a new `NomChain` covering all of the provided segments, in
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use nombytes::NomChain;
+    ///
+    /// let chain = NomChain::new(vec![Bytes::from("hello, "), Bytes::from("world")]);
+    /// assert_eq!(chain.len(), 12);
+    /// ```
+    pub fn new<I>(segments: I) -> Self
+    where
+        I: IntoIterator<Item = Bytes>,
+    {
+        let mut len = 0;
+        let segments: Vec<_> = segments
+            .into_iter()
+            // Empty segments can't contain anything, and leaving them out
+            // means every position maps to exactly one segment.
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| {
+                let segment = Segment { start: len, bytes };
+                len += segment.bytes.len();
+                segment
+            })
+            .collect();
+
+        Self {
+            segments: segments.into(),
+            start: 0,
+            end: len,
+        }
+    }
+
+    /// Returns the number of bytes this chain covers.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns `true` if this chain covers no bytes.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the byte at `index`, or `None` if it's out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use nombytes::NomChain;
+    ///
+    /// let chain = NomChain::new(vec![Bytes::from("ab"), Bytes::from("cd")]);
+    /// assert_eq!(chain.get(2), Some(b'c'));
+    /// assert_eq!(chain.get(4), None);
+    /// ```
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<u8> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let pos = self.start + index;
+        let segment = &self.segments[self.segment_index(pos)];
+        Some(segment.bytes[pos - segment.start])
+    }
+
+    /// Returns an iterator over the bytes this chain covers.
+    #[inline]
+    #[must_use]
+    pub fn iter(&self) -> NomChainIter {
+        NomChainIter {
+            segments: Arc::clone(&self.segments),
+            segment: self.segment_index(self.start),
+            pos: self.start,
+            end: self.end,
+        }
+    }
+
+    /// Returns an iterator over the parts of the segments this chain covers,
+    /// without copying any of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use nombytes::NomChain;
+    ///
+    /// let chain = NomChain::new(vec![Bytes::from("hello, "), Bytes::from("world")]);
+    /// let chunks: Vec<_> = chain.slice_range(3..9).segments().collect();
+    /// assert_eq!(chunks, ["lo, ", "wo"]);
+    /// ```
+    pub fn segments(&self) -> impl Iterator<Item = Bytes> + '_ {
+        let (start, end) = (self.start, self.end);
+        self.segments[self.segment_index(start)..]
+            .iter()
+            .take_while(move |segment| segment.start < end)
+            .map(move |segment| {
+                let from = start.saturating_sub(segment.start);
+                let to = (end - segment.start).min(segment.bytes.len());
+                segment.bytes.slice(from..to)
+            })
+    }
+
+    /// Returns the bytes this chain covers as a [`NomBytes`].
+    ///
+    /// If they're all in one segment, the returned value shares memory with
+    /// that segment. Otherwise, they're copied into a new buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use nombytes::NomChain;
+    ///
+    /// let chain = NomChain::new(vec![Bytes::from("hello, "), Bytes::from("world")]);
+    /// assert_eq!(chain.slice_range(..5).to_nom_bytes().to_str(), "hello");
+    /// assert_eq!(chain.slice_range(5..).to_nom_bytes().to_str(), ", world");
+    /// ```
+    #[must_use]
+    pub fn to_nom_bytes(&self) -> NomBytes {
+        let mut segments = self.segments();
+        let first = match segments.next() {
+            Some(first) => first,
+            None => return NomBytes::new(Bytes::new()),
+        };
+        if first.len() == self.len() {
+            return NomBytes::new(first);
+        }
+
+        let mut buf = BytesMut::with_capacity(self.len());
+        buf.extend_from_slice(&first);
+        for segment in segments {
+            buf.extend_from_slice(&segment);
+        }
+        NomBytes::new(buf.freeze())
+    }
+
+    /// Returns a new `NomChain` covering `range` of this one, without
+    /// copying anything.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or its start is after its end.
+    #[must_use]
+    pub fn slice_range<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let len = self.len();
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n + 1,
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end,
+            "range start must not be greater than end: {start:?} <= {end:?}",
+        );
+        assert!(end <= len, "range end out of bounds: {end:?} <= {len:?}");
+
+        Self {
+            segments: Arc::clone(&self.segments),
+            start: self.start + start,
+            end: self.start + end,
+        }
+    }
+
+    /// Returns the offset of `second` relative to `self` in the concatenated
+    /// input. This is the implementation behind the `Offset` impls for the
+    /// supported `nom` versions.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    #[inline]
+    pub(crate) fn offset_to(&self, second: &Self) -> usize {
+        second.start - self.start
+    }
+
+    /// Returns the index of the first byte matching `predicate`.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(u8) -> bool,
+    {
+        self.iter().position(predicate)
+    }
+
+    /// Compares the start of this chain with `tag`, using `eq` to compare the
+    /// individual bytes.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn compare_with<F>(&self, tag: &[u8], eq: F) -> Comparison
+    where
+        F: Fn(u8, u8) -> bool,
+    {
+        let mut bytes = self.iter();
+        for &expected in tag {
+            match bytes.next() {
+                Some(b) if eq(b, expected) => {}
+                Some(_) => return Comparison::Error,
+                None => return Comparison::Incomplete,
+            }
+        }
+        Comparison::Ok
+    }
+
+    /// Returns the index of the first occurrence of `needle` in this chain.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn find(&self, needle: &[u8]) -> Option<usize> {
+        let first = match needle.first() {
+            Some(&first) => first,
+            None => return Some(0),
+        };
+
+        let last_start = self.len().checked_sub(needle.len())?;
+        let mut rest = self.clone();
+        let mut skipped = 0;
+        loop {
+            let i = rest.position(|b| b == first)?;
+            if skipped + i > last_start {
+                return None;
+            }
+            rest = rest.slice_range(i..);
+            if rest.compare_with(needle, |a, b| a == b) == Comparison::Ok {
+                return Some(skipped + i);
+            }
+            rest = rest.slice_range(1..);
+            skipped += i + 1;
+        }
+    }
+
+    /// Returns the index of the segment the byte at absolute position `pos`
+    /// is in, or the number of segments if `pos` is past the last one.
+    fn segment_index(&self, pos: usize) -> usize {
+        self.segments
+            .partition_point(|segment| segment.start + segment.bytes.len() <= pos)
+    }
+}
+
+/// The result of [`NomChain::compare_with`], mirroring `nom`'s
+/// `CompareResult`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+pub(crate) enum Comparison {
+    Ok,
+    Incomplete,
+    Error,
+}
+
+impl IntoIterator for &NomChain {
+    type Item = u8;
+    type IntoIter = NomChainIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Bytes> for NomChain {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Bytes>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl From<Bytes> for NomChain {
+    #[inline]
+    fn from(bytes: Bytes) -> Self {
+        Self::new(core::iter::once(bytes))
+    }
+}
+
+impl From<NomBytes> for NomChain {
+    #[inline]
+    fn from(nom_bytes: NomBytes) -> Self {
+        Self::from(nom_bytes.into_bytes())
+    }
+}
+
+impl From<Vec<Bytes>> for NomChain {
+    #[inline]
+    fn from(segments: Vec<Bytes>) -> Self {
+        Self::new(segments)
+    }
+}
+
+// Like `NomBytes`, equality and ordering are in terms of the contents.
+
+impl PartialEq for NomChain {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+impl Eq for NomChain {}
+
+impl PartialOrd for NomChain {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for NomChain {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+/// An iterator over the bytes of a [`NomChain`].
+///
+/// This struct is created by [`NomChain::iter`]. It holds on to the
+/// segments, so it doesn't borrow the chain.
+#[derive(Clone, Debug)]
+pub struct NomChainIter {
+    segments: Arc<[Segment]>,
+    segment: usize,
+    pos: usize,
+    end: usize,
+}
+
+impl Iterator for NomChainIter {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.pos >= self.end {
+            return None;
+        }
+
+        let mut segment = &self.segments[self.segment];
+        if self.pos >= segment.start + segment.bytes.len() {
+            self.segment += 1;
+            segment = &self.segments[self.segment];
+        }
+        let b = segment.bytes[self.pos - segment.start];
+        self.pos += 1;
+        Some(b)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.pos;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for NomChainIter {}
+
+#[cfg(feature = "nom7")]
+mod nom7 {
+    use super::{Comparison, NomChain, NomChainIter};
+    use core::iter::Enumerate;
+    use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
+    use nom::error::{ErrorKind, ParseError};
+    use nom::{
+        Compare, CompareResult, FindSubstring, IResult, InputIter, InputLength, InputTake,
+        InputTakeAtPosition, Needed, Offset, Slice,
+    };
+
+    impl From<Comparison> for CompareResult {
+        #[inline]
+        fn from(comparison: Comparison) -> Self {
+            match comparison {
+                Comparison::Ok => CompareResult::Ok,
+                Comparison::Incomplete => CompareResult::Incomplete,
+                Comparison::Error => CompareResult::Error,
+            }
+        }
+    }
+
+    impl InputIter for NomChain {
+        type Item = u8;
+        type Iter = Enumerate<Self::IterElem>;
+        type IterElem = NomChainIter;
+
+        #[inline]
+        fn iter_indices(&self) -> Self::Iter {
+            self.iter().enumerate()
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::IterElem {
+            self.iter()
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            NomChain::position(self, predicate)
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            if self.len() >= count {
+                Ok(count)
+            } else {
+                Err(Needed::new(count - self.len()))
+            }
+        }
+    }
+
+    impl InputTake for NomChain {
+        #[inline]
+        fn take(&self, count: usize) -> Self {
+            self.slice_range(..count)
+        }
+
+        #[inline]
+        fn take_split(&self, count: usize) -> (Self, Self) {
+            (self.slice_range(count..), self.slice_range(..count))
+        }
+    }
+
+    impl InputTakeAtPosition for NomChain {
+        type Item = u8;
+
+        fn split_at_position<P, E: ParseError<Self>>(&self, predicate: P) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match NomChain::position(self, predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position1<P, E: ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: ErrorKind,
+        ) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match NomChain::position(self, predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position_complete<P, E: ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match NomChain::position(self, predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Ok(self.take_split(self.len())),
+            }
+        }
+
+        fn split_at_position1_complete<P, E: ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: ErrorKind,
+        ) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match NomChain::position(self, predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None if self.is_empty() => {
+                    Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+                }
+                None => Ok(self.take_split(self.len())),
+            }
+        }
+    }
+
+    impl InputLength for NomChain {
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.len()
+        }
+    }
+
+    macro_rules! nom_chain_slice {
+        ($range_ty:ty) => {
+            impl Slice<$range_ty> for NomChain {
+                #[inline]
+                fn slice(&self, range: $range_ty) -> Self {
+                    self.slice_range(range)
+                }
+            }
+        };
+    }
+
+    nom_chain_slice!(Range<usize>);
+    nom_chain_slice!(RangeTo<usize>);
+    nom_chain_slice!(RangeFrom<usize>);
+    nom_chain_slice!(RangeFull);
+
+    impl Offset for NomChain {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.offset_to(second)
+        }
+    }
+
+    impl Compare<&'_ [u8]> for NomChain {
+        #[inline]
+        fn compare(&self, t: &[u8]) -> CompareResult {
+            self.compare_with(t, |a, b| a == b).into()
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &[u8]) -> CompareResult {
+            self.compare_with(t, |a, b| a.eq_ignore_ascii_case(&b))
+                .into()
+        }
+    }
+
+    impl Compare<&'_ str> for NomChain {
+        #[inline]
+        fn compare(&self, t: &str) -> CompareResult {
+            self.compare(t.as_bytes())
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &str) -> CompareResult {
+            self.compare_no_case(t.as_bytes())
+        }
+    }
+
+    impl FindSubstring<&'_ [u8]> for NomChain {
+        fn find_substring(&self, substr: &[u8]) -> Option<usize> {
+            self.find(substr)
+        }
+    }
+
+    impl FindSubstring<&'_ str> for NomChain {
+        fn find_substring(&self, substr: &str) -> Option<usize> {
+            self.find(substr.as_bytes())
+        }
+    }
+}
+
+#[cfg(feature = "nom8")]
+mod nom8 {
+    use super::{Comparison, NomChain, NomChainIter};
+    use core::iter::Enumerate;
+    use nom8::{Compare, CompareResult, FindSubstring, Input, Needed, Offset};
+
+    impl From<Comparison> for CompareResult {
+        #[inline]
+        fn from(comparison: Comparison) -> Self {
+            match comparison {
+                Comparison::Ok => CompareResult::Ok,
+                Comparison::Incomplete => CompareResult::Incomplete,
+                Comparison::Error => CompareResult::Error,
+            }
+        }
+    }
+
+    impl Input for NomChain {
+        type Item = u8;
+        type Iter = NomChainIter;
+        type IterIndices = Enumerate<Self::Iter>;
+
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.len()
+        }
+
+        #[inline]
+        fn take(&self, index: usize) -> Self {
+            self.slice_range(..index)
+        }
+
+        #[inline]
+        fn take_from(&self, index: usize) -> Self {
+            self.slice_range(index..)
+        }
+
+        #[inline]
+        fn take_split(&self, index: usize) -> (Self, Self) {
+            (self.slice_range(index..), self.slice_range(..index))
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            NomChain::position(self, predicate)
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::Iter {
+            self.iter()
+        }
+
+        #[inline]
+        fn iter_indices(&self) -> Self::IterIndices {
+            self.iter().enumerate()
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            if self.len() >= count {
+                Ok(count)
+            } else {
+                Err(Needed::new(count - self.len()))
+            }
+        }
+    }
+
+    impl Offset for NomChain {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.offset_to(second)
+        }
+    }
+
+    impl Compare<&'_ [u8]> for NomChain {
+        #[inline]
+        fn compare(&self, t: &[u8]) -> CompareResult {
+            self.compare_with(t, |a, b| a == b).into()
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &[u8]) -> CompareResult {
+            self.compare_with(t, |a, b| a.eq_ignore_ascii_case(&b))
+                .into()
+        }
+    }
+
+    impl Compare<&'_ str> for NomChain {
+        #[inline]
+        fn compare(&self, t: &str) -> CompareResult {
+            self.compare(t.as_bytes())
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &str) -> CompareResult {
+            self.compare_no_case(t.as_bytes())
+        }
+    }
+
+    impl FindSubstring<&'_ [u8]> for NomChain {
+        fn find_substring(&self, substr: &[u8]) -> Option<usize> {
+            self.find(substr)
+        }
+    }
+
+    impl FindSubstring<&'_ str> for NomChain {
+        fn find_substring(&self, substr: &str) -> Option<usize> {
+            self.find(substr.as_bytes())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::NomChain;
+        use bytes::Bytes;
+        use nom8::bytes::streaming::{tag, take_until};
+        use nom8::number::streaming::be_u32;
+        use nom8::{Offset, Parser};
+
+        type Error = nom8::error::Error<NomChain>;
+
+        #[test]
+        fn parses_across_segments() {
+            let input = NomChain::from_iter([
+                Bytes::from_static(b"\x00\x00"),
+                Bytes::from_static(b"\x01\x00key"),
+                Bytes::from_static(b"=val\n"),
+            ]);
+
+            let (rest, n) = be_u32::<_, Error>(input.clone()).unwrap();
+            let (rest, key) = take_until::<_, _, Error>("=").parse(rest).unwrap();
+            let (rest, _) = tag::<_, _, Error>("=").parse(rest).unwrap();
+
+            assert_eq!(n, 256);
+            assert_eq!(key.to_nom_bytes().to_str(), "key");
+            assert_eq!(input.offset(&rest), 8);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "nom7"))]
+mod tests {
+    use crate::NomChain;
+    use bytes::Bytes;
+    use nom::bytes::streaming::{tag, take_until, take_while1};
+    use nom::number::streaming::be_u16;
+    use nom::{IResult, Offset, Slice};
+
+    fn chain(segments: &[&'static str]) -> NomChain {
+        segments
+            .iter()
+            .map(|s| Bytes::from_static(s.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_across_segments() {
+        fn key_value(input: NomChain) -> IResult<NomChain, (NomChain, NomChain)> {
+            let (input, key) = take_while1(|b: u8| b.is_ascii_alphabetic())(input)?;
+            let (input, _) = tag("=")(input)?;
+            let (input, value) = take_until("\r\n")(input)?;
+            let (input, _) = tag("\r\n")(input)?;
+            Ok((input, (key, value)))
+        }
+
+        let input = chain(&["na", "me=ferr", "is", "\r", "\nrest"]);
+        let (rest, (key, value)) = key_value(input.clone()).unwrap();
+
+        assert_eq!(key.to_nom_bytes().to_str(), "name");
+        assert_eq!(value.to_nom_bytes().to_str(), "ferris");
+        assert_eq!(input.offset(&value), 5);
+        assert_eq!(input.offset(&rest), 13);
+        assert_eq!(rest.to_nom_bytes().to_str(), "rest");
+    }
+
+    #[test]
+    fn streaming_parsers_ask_for_more() {
+        let input = chain(&["\x00"]);
+        assert_eq!(
+            be_u16::<_, nom::error::Error<NomChain>>(input),
+            Err(nom::Err::Incomplete(nom::Needed::new(1)))
+        );
+
+        let input = chain(&["ab", "c"]);
+        assert!(matches!(
+            take_until::<_, _, nom::error::Error<NomChain>>("cd")(input),
+            Err(nom::Err::Incomplete(_))
+        ));
+    }
+
+    #[test]
+    fn only_copies_across_segment_boundaries() {
+        let first = Bytes::from_static(b"hello, ");
+        let input = NomChain::from_iter([first.clone(), Bytes::from_static(b"world")]);
+
+        let within = input.slice(1..5).to_nom_bytes();
+        assert_eq!(within.as_bytes(), b"ello");
+        assert_eq!(within.as_bytes().as_ptr(), first[1..].as_ptr());
+
+        let across = input.slice(5..9).to_nom_bytes();
+        assert_eq!(across.as_bytes(), b", wo");
+    }
+
+    #[test]
+    fn empty_segments_and_slices_work() {
+        let input = chain(&["", "ab", "", "", "cd", ""]);
+        assert_eq!(input.len(), 4);
+        assert_eq!(input.get(2), Some(b'c'));
+        assert!(input.iter().eq(*b"abcd"));
+
+        let end = input.slice(4..);
+        assert!(end.is_empty());
+        assert_eq!(input.offset(&end), 4);
+        assert_eq!(end.slice(..).to_nom_bytes().as_bytes(), b"");
+        assert!(chain(&[]).is_empty());
+    }
+
+    #[test]
+    fn compares_by_contents() {
+        assert_eq!(chain(&["ab", "cd"]), chain(&["a", "bcd"]));
+        assert!(chain(&["ab", "c"]) < chain(&["abd"]));
+    }
+}
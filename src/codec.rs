@@ -0,0 +1,371 @@
+use crate::stream::needed_len;
+use crate::NomBytes;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use nom::IResult;
+use tokio_util::codec::Decoder;
+
+/// A [`Decoder`] that decodes frames by running a streaming `nom` parser
+/// over the buffered bytes as a [`NomBytes`].
+///
+/// The buffered bytes are frozen into a [`NomBytes`] once, and the parser
+/// slices frames out of it without copying them. What's left after a frame
+/// stays frozen in the decoder, since there's a good chance it holds more
+/// complete frames, and only goes back into the read buffer when the parser
+/// returns [`nom::Err::Incomplete`]. The decoder then asks for more data by
+/// returning `Ok(None)`, and doesn't run the parser again until at least as
+/// many bytes as it said it needed have arrived.
+///
+/// The parser can use any error type. Errors are handed back in
+/// [`NomDecoderError::Parse`], and the bytes the parser failed on are put
+/// back into the read buffer.
+///
+/// # Examples
+///
+/// ```
+/// use futures::StreamExt;
+/// use nom::multi::length_data;
+/// use nom::number::streaming::be_u8;
+/// use nom::IResult;
+/// use nombytes::{NomBytes, NomDecoder};
+/// use tokio_util::codec::FramedRead;
+///
+/// fn frame(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+///     length_data(be_u8)(input)
+/// }
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let input = &b"\x05hello\x05world"[..];
+/// let mut frames = FramedRead::new(input, NomDecoder::new(frame));
+///
+/// assert_eq!(frames.next().await.unwrap().unwrap().to_str(), "hello");
+/// assert_eq!(frames.next().await.unwrap().unwrap().to_str(), "world");
+/// assert!(frames.next().await.is_none());
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct NomDecoder<P> {
+    parser: P,
+    // The unparsed bytes left over after the last frame. They're kept here
+    // rather than in the read buffer so that they don't need to be frozen,
+    // and copied, again for the next frame.
+    remaining: Bytes,
+    // The stream offset of the first unparsed byte.
+    position: usize,
+    // How many bytes the parser needs before it's worth running it again.
+    needed: usize,
+}
+
+impl<P> NomDecoder<P> {
+    /// Creates a new `NomDecoder` that decodes frames using `parser`.
+    #[inline]
+    pub fn new(parser: P) -> Self {
+        Self {
+            parser,
+            remaining: Bytes::new(),
+            position: 0,
+            needed: 0,
+        }
+    }
+
+    /// Returns how many bytes of the stream have been consumed by decoded
+    /// frames so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns a reference to the parser.
+    #[inline]
+    pub fn get_ref(&self) -> &P {
+        &self.parser
+    }
+
+    /// Returns a mutable reference to the parser.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut P {
+        &mut self.parser
+    }
+
+    /// Returns the parser.
+    #[inline]
+    pub fn into_inner(self) -> P {
+        self.parser
+    }
+}
+
+impl<P, O, E> Decoder for NomDecoder<P>
+where
+    P: FnMut(NomBytes) -> IResult<NomBytes, O, E>,
+{
+    type Item = O;
+    type Error = NomDecoderError<E>;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if self.remaining.is_empty() {
+            if src.is_empty() || src.len() < self.needed {
+                return Ok(None);
+            }
+            self.remaining = src.split().freeze();
+        } else if !src.is_empty() {
+            // More data was added to the read buffer while the decoder still
+            // held on to bytes left over after a frame.
+            let mut buf = BytesMut::from(core::mem::take(&mut self.remaining));
+            buf.extend_from_slice(src);
+            src.clear();
+            self.remaining = buf.freeze();
+        }
+
+        let input = NomBytes::new(core::mem::take(&mut self.remaining));
+        self.needed = 0;
+        match (self.parser)(input.clone()) {
+            Ok((rest, item)) => {
+                let consumed = input.offset_to(&rest);
+                if consumed == 0 {
+                    restore(src, input);
+                    return Err(NomDecoderError::NoProgress {
+                        offset: self.position,
+                    });
+                }
+                self.position += consumed;
+                self.remaining = rest.into_bytes();
+                Ok(Some(item))
+            }
+            Err(nom::Err::Incomplete(needed)) => {
+                self.needed = needed_len(input.as_bytes().len(), needed);
+                restore(src, input);
+                src.reserve(self.needed.saturating_sub(src.len()));
+                Ok(None)
+            }
+            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+                restore(src, input);
+                Err(NomDecoderError::Parse {
+                    offset: self.position,
+                    error,
+                })
+            }
+        }
+    }
+}
+
+/// Puts the unparsed bytes back into the (empty) read buffer. Unless a
+/// decoded frame still shares them, this reuses their memory rather than
+/// copying them.
+fn restore(src: &mut BytesMut, input: NomBytes) {
+    debug_assert!(src.is_empty());
+    // The read buffer may still share the allocation, which would make
+    // converting the bytes back copy them.
+    drop(core::mem::take(src));
+    *src = BytesMut::from(input.into_bytes());
+}
+
+/// The error type of [`NomDecoder`].
+///
+/// `E` is the error type of the parser.
+#[derive(Debug)]
+pub enum NomDecoderError<E = nom::error::Error<NomBytes>> {
+    /// Reading from the underlying I/O object failed.
+    Io(std::io::Error),
+    /// The parser failed to parse the buffered bytes.
+    Parse {
+        /// The offset in the stream the frame being parsed starts at.
+        ///
+        /// The parser was given the buffered bytes from there on as a fresh
+        /// [`NomBytes`], so positions in its error, like
+        /// [`NomBytes::offset_in_origin`] of the input it failed on, count
+        /// from this offset.
+        offset: usize,
+        /// The error the parser returned.
+        error: E,
+    },
+    /// The parser returned a frame without consuming any input. Running it
+    /// again would return the same frame forever, so the decoder stops
+    /// instead.
+    NoProgress {
+        /// The offset in the stream the parser was run at.
+        offset: usize,
+    },
+}
+
+impl<E: Display> Display for NomDecoderError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NomDecoderError::Io(e) => write!(f, "I/O error while decoding: {e}"),
+            NomDecoderError::Parse { offset, error } => {
+                write!(f, "parse error at offset {offset} while decoding: {error}")
+            }
+            NomDecoderError::NoProgress { offset } => write!(
+                f,
+                "parser returned a frame at offset {offset} without consuming any input"
+            ),
+        }
+    }
+}
+
+impl<E> std::error::Error for NomDecoderError<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NomDecoderError::Io(e) => Some(e),
+            NomDecoderError::Parse { error, .. } => Some(error),
+            NomDecoderError::NoProgress { .. } => None,
+        }
+    }
+}
+
+impl<E> From<std::io::Error> for NomDecoderError<E> {
+    #[inline]
+    fn from(e: std::io::Error) -> Self {
+        NomDecoderError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NomDecoder, NomDecoderError};
+    use crate::NomBytes;
+    use bytes::BytesMut;
+    use futures::StreamExt;
+    use nom::bytes::streaming::{tag, take_until};
+    use nom::error::ErrorKind;
+    use nom::multi::length_data;
+    use nom::number::streaming::be_u16;
+    use nom::sequence::terminated;
+    use nom::IResult;
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::{Decoder, FramedRead};
+
+    fn line(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+        terminated(take_until("\n"), tag("\n"))(input)
+    }
+
+    fn frame(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+        length_data(be_u16)(input)
+    }
+
+    #[tokio::test]
+    async fn decodes_frames_split_across_reads() {
+        let (mut writer, reader) = tokio::io::duplex(4);
+        let mut frames = FramedRead::new(reader, NomDecoder::new(frame));
+
+        tokio::spawn(async move {
+            writer
+                .write_all(b"\x00\x0bhello world\x00\x00\x00\x03end")
+                .await
+                .unwrap();
+        });
+
+        let mut decoded = Vec::new();
+        while let Some(frame) = frames.next().await {
+            decoded.push(frame.unwrap().to_str().to_owned());
+        }
+        assert_eq!(decoded, vec!["hello world", "", "end"]);
+    }
+
+    #[tokio::test]
+    async fn reports_leftover_bytes_at_eof() {
+        let input = &b"one\ntwo"[..];
+        let mut frames = FramedRead::new(input, NomDecoder::new(line));
+
+        assert_eq!(frames.next().await.unwrap().unwrap().to_str(), "one");
+        assert!(matches!(
+            frames.next().await.unwrap(),
+            Err(NomDecoderError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn waits_for_needed_bytes() {
+        let mut decoder = NomDecoder::new(frame);
+        let mut src = BytesMut::from(&b"\x00\x05he"[..]);
+
+        assert!(decoder.decode(&mut src).unwrap().is_none());
+        assert_eq!(decoder.needed, 7);
+        assert_eq!(&src[..], b"\x00\x05he");
+
+        src.extend_from_slice(b"llo\x00");
+        let frame = decoder.decode(&mut src).unwrap().unwrap();
+        assert_eq!(frame.to_str(), "hello");
+        assert!(decoder.decode(&mut src).unwrap().is_none());
+        assert_eq!(&src[..], b"\x00");
+    }
+
+    #[test]
+    fn parse_errors_carry_the_offset() {
+        fn greeting(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+            let (input, _) = line(input)?;
+            tag("hello")(input)
+        }
+
+        let mut decoder = NomDecoder::new(greeting);
+        let mut src = BytesMut::from(&b"skip me\nhello skip me\ngoodbye"[..]);
+
+        assert_eq!(decoder.decode(&mut src).unwrap().unwrap().to_str(), "hello");
+        match decoder.decode(&mut src) {
+            Err(NomDecoderError::Parse { offset, error }) => {
+                assert_eq!(offset, 13);
+                assert_eq!(error.input.offset_in_origin(), 9);
+                assert_eq!(error.code, ErrorKind::Tag);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        // The bytes the parser failed on are put back.
+        assert_eq!(&src[..], b" skip me\ngoodbye");
+        assert_eq!(decoder.position(), 13);
+    }
+
+    #[test]
+    fn frames_share_the_buffered_bytes() {
+        let mut decoder = NomDecoder::new(frame);
+        let mut src = BytesMut::from(&b"\x00\x01a\x00\x01b\x00\x01"[..]);
+
+        let a = decoder.decode(&mut src).unwrap().unwrap();
+        let b = decoder.decode(&mut src).unwrap().unwrap();
+        // Both were sliced out of the same frozen buffer.
+        assert_eq!(b.as_bytes().as_ptr(), a.as_bytes().as_ptr().wrapping_add(3));
+
+        assert!(decoder.decode(&mut src).unwrap().is_none());
+        src.extend_from_slice(b"c");
+        let c = decoder.decode(&mut src).unwrap().unwrap();
+        assert_eq!(c.to_str(), "c");
+        assert_eq!(decoder.position(), 9);
+    }
+
+    #[test]
+    fn rejects_parsers_that_consume_nothing() {
+        fn nothing(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+            take_until("\n")(input)
+        }
+
+        let mut decoder = NomDecoder::new(nothing);
+        let mut src = BytesMut::from(&b"\nrest"[..]);
+
+        assert!(matches!(
+            decoder.decode(&mut src),
+            Err(NomDecoderError::NoProgress { offset: 0 })
+        ));
+        assert_eq!(&src[..], b"\nrest");
+    }
+
+    #[test]
+    fn accepts_other_error_types() {
+        fn greeting(input: NomBytes) -> IResult<NomBytes, NomBytes, crate::error::Error> {
+            tag("hello")(input)
+        }
+
+        let mut decoder = NomDecoder::new(greeting);
+        let mut src = BytesMut::from(&b"hello goodbye"[..]);
+
+        assert_eq!(decoder.decode(&mut src).unwrap().unwrap().to_str(), "hello");
+        match decoder.decode(&mut src) {
+            Err(NomDecoderError::Parse { offset, error }) => {
+                assert_eq!(offset, 5);
+                assert_eq!(error.kind(), ErrorKind::Tag);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(&src[..], b" goodbye");
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{LineIndex, NomBytes, NomStr};
+use codespan_reporting::diagnostic::Label;
+use codespan_reporting::files::{Error, Files};
+use core::fmt::Display;
+use core::ops::Range;
+use core::str::Utf8Error;
+use once_cell::sync::OnceCell;
+
+/// A `codespan_reporting` [`Files`] database of named [`NomBytes`] sources.
+///
+/// Sources are stored as they are, without being copied, and the start of
+/// each line is only worked out the first time a diagnostic needs it. Since
+/// the values you parse out of a source are slices of it, they can be turned
+/// straight into diagnostic labels with [`primary_label`](Self::primary_label)
+/// and [`secondary_label`](Self::secondary_label) without having to keep
+/// track of which file they came from.
+///
+/// `codespan_reporting` wants its sources as text, so only sources that are
+/// valid UTF-8 can be added. Decoding the others lossily would move every
+/// label past the first invalid byte.
+///
+/// # Examples
+///
+/// ```
+/// use codespan_reporting::diagnostic::Diagnostic;
+/// use codespan_reporting::term::{self, Config};
+/// use nombytes::{NomBytes, NomFiles};
+///
+/// let mut files = NomFiles::new();
+/// let a = NomBytes::from("name = \"a\"\nport = 80\n");
+/// let b = NomBytes::from("name = \"b\"\nport = eighty\n");
+/// files.add("a.toml", a)?;
+/// files.add("b.toml", b.clone())?;
+///
+/// // Pretend a parser handed us this slice of `b.toml` as a bad value
+/// let port = NomBytes::new(b.to_bytes().slice(18..24));
+/// let diagnostic = Diagnostic::error()
+///     .with_message("invalid port")
+///     .with_labels(vec![files.primary_label(&port).unwrap().with_message("not a number")]);
+///
+/// let output = term::emit_into_string(&Config::default(), &files, &diagnostic)?;
+/// assert!(output.contains("b.toml:2:8"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct NomFiles<Name = String> {
+    files: Vec<NomFile<Name>>,
+}
+
+#[derive(Debug)]
+struct NomFile<Name> {
+    name: Name,
+    text: NomStr,
+    lines: OnceCell<LineIndex>,
+}
+
+impl<Name> NomFile<Name> {
+    fn lines(&self) -> &LineIndex {
+        self.lines
+            .get_or_init(|| LineIndex::new(self.text.as_nom_bytes()))
+    }
+}
+
+impl<Name> NomFiles<Name> {
+    /// Creates a new, empty `NomFiles`.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self { files: Vec::new() }
+    }
+
+    /// Adds a source called `name` and returns the id it can be looked up
+    /// by.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `source` isn't valid UTF-8.
+    pub fn add(&mut self, name: Name, source: NomBytes) -> Result<usize, Utf8Error> {
+        let id = self.files.len();
+        self.files.push(NomFile {
+            name,
+            text: NomStr::try_from(source)?,
+            lines: OnceCell::new(),
+        });
+        Ok(id)
+    }
+
+    /// Returns the name and contents of the source with the given id.
+    #[must_use]
+    pub fn get(&self, id: usize) -> Option<(&Name, &NomBytes)> {
+        let file = self.files.get(id)?;
+        Some((&file.name, file.text.as_nom_bytes()))
+    }
+
+    /// Returns the number of sources.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if there are no sources.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Finds the source `slice` is a slice of, and returns its id together
+    /// with the byte range `slice` covers in it, or `None` if it isn't a
+    /// slice of any of them.
+    #[must_use]
+    pub fn find(&self, slice: &NomBytes) -> Option<(usize, Range<usize>)> {
+        self.files.iter().enumerate().find_map(|(id, file)| {
+            let start = file.text.as_nom_bytes().try_offset(slice)?;
+            Some((id, start..start + slice.as_bytes().len()))
+        })
+    }
+
+    /// Returns a primary [`Label`] covering `slice`, or `None` if it isn't a
+    /// slice of any of the sources.
+    #[must_use]
+    pub fn primary_label(&self, slice: &NomBytes) -> Option<Label<usize>> {
+        let (id, range) = self.find(slice)?;
+        Some(Label::primary(id, range))
+    }
+
+    /// Returns a secondary [`Label`] covering `slice`, or `None` if it isn't a
+    /// slice of any of the sources.
+    #[must_use]
+    pub fn secondary_label(&self, slice: &NomBytes) -> Option<Label<usize>> {
+        let (id, range) = self.find(slice)?;
+        Some(Label::secondary(id, range))
+    }
+
+    fn file(&self, id: usize) -> Result<&NomFile<Name>, Error> {
+        self.files.get(id).ok_or(Error::FileMissing)
+    }
+}
+
+impl<Name> Default for NomFiles<Name> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, Name: 'a + Display> Files<'a> for NomFiles<Name> {
+    type FileId = usize;
+    type Name = &'a Name;
+    type Source = &'a str;
+
+    fn name(&'a self, id: usize) -> Result<&'a Name, Error> {
+        Ok(&self.file(id)?.name)
+    }
+
+    fn source(&'a self, id: usize) -> Result<&'a str, Error> {
+        Ok(self.file(id)?.text.as_str())
+    }
+
+    fn line_index(&'a self, id: usize, byte_index: usize) -> Result<usize, Error> {
+        let file = self.file(id)?;
+        match file.lines().line_col(byte_index) {
+            Some(lc) => Ok(lc.line - 1),
+            None => Err(Error::IndexTooLarge {
+                given: byte_index,
+                max: file.text.as_str().len(),
+            }),
+        }
+    }
+
+    fn line_range(&'a self, id: usize, line_index: usize) -> Result<Range<usize>, Error> {
+        let lines = self.file(id)?.lines();
+        lines.line_range(line_index + 1).ok_or(Error::LineTooLarge {
+            given: line_index,
+            max: lines.line_count() - 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NomFiles;
+    use crate::NomBytes;
+    use codespan_reporting::diagnostic::{Diagnostic, LabelStyle};
+    use codespan_reporting::files::{Error, Files, Location};
+    use codespan_reporting::term::{emit_into_string, Config};
+
+    #[test]
+    fn builds_line_index_lazily() {
+        let mut files = NomFiles::new();
+        let id = files
+            .add("a", NomBytes::from("one\ntwo\r\n\nthree"))
+            .unwrap();
+        assert!(files.files[id].lines.get().is_none());
+
+        assert_eq!(files.line_index(id, 0).unwrap(), 0);
+        assert_eq!(files.files[id].lines.get().unwrap().line_count(), 4);
+        assert_eq!(files.line_index(id, 5).unwrap(), 1);
+        assert_eq!(files.line_index(id, 15).unwrap(), 3);
+        assert_eq!(files.line_range(id, 1).unwrap(), 4..9);
+        assert_eq!(files.line_range(id, 3).unwrap(), 10..15);
+        assert!(matches!(
+            files.line_range(id, 4),
+            Err(Error::LineTooLarge { given: 4, max: 3 })
+        ));
+        assert!(matches!(
+            files.line_index(id, 16),
+            Err(Error::IndexTooLarge { given: 16, max: 15 })
+        ));
+        assert_eq!(
+            files.location(id, 11).unwrap(),
+            Location {
+                line_number: 4,
+                column_number: 2
+            }
+        );
+    }
+
+    #[test]
+    fn looks_up_files() {
+        let mut files = NomFiles::new();
+        assert!(files.is_empty());
+        let a = files.add("a", NomBytes::from("alpha")).unwrap();
+        let b = files.add("b", NomBytes::from("beta")).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(*files.name(a).unwrap(), "a");
+        assert_eq!(files.source(b).unwrap(), "beta");
+        assert_eq!(files.get(b).unwrap().1.as_bytes(), b"beta");
+        assert!(matches!(files.name(2), Err(Error::FileMissing)));
+
+        // Sources that aren't UTF-8 are turned away
+        assert!(files.add("c", NomBytes::from(&b"b\xffta"[..])).is_err());
+        assert_eq!(files.len(), 2);
+    }
+
+    #[test]
+    fn finds_slices_in_their_source() {
+        let a = NomBytes::from("alpha");
+        let b = NomBytes::from("beta");
+        let mut files = NomFiles::new();
+        files.add("a", a.clone()).unwrap();
+        let b_id = files.add("b", b.clone()).unwrap();
+
+        assert_eq!(files.find(&b.slice_range(1..3)), Some((b_id, 1..3)));
+        assert_eq!(files.find(&b.slice_range(4..)), Some((b_id, 4..4)));
+        assert_eq!(files.find(&NomBytes::from("beta")), None);
+
+        let label = files.secondary_label(&a.slice_range(2..)).unwrap();
+        assert_eq!(label.style, LabelStyle::Secondary);
+        assert_eq!((label.file_id, label.range), (0, 2..5));
+    }
+
+    #[test]
+    fn renders_diagnostics() {
+        let mut files = NomFiles::new();
+        let source = NomBytes::from("x = 1\ny = two\n");
+        files.add("vars.txt".to_owned(), source.clone()).unwrap();
+
+        let value = source.slice_range(10..13);
+        let diagnostic = Diagnostic::error()
+            .with_message("expected a number")
+            .with_labels(vec![files.primary_label(&value).unwrap()]);
+        let output = emit_into_string(&Config::default(), &files, &diagnostic).unwrap();
+
+        assert!(output.contains("vars.txt:2:5"), "{output}");
+        assert!(output.contains("y = two"), "{output}");
+    }
+}
@@ -0,0 +1,380 @@
+//! An error type for parsing [`NomBytes`] that doesn't hold on to the input.
+
+use crate::{NomBits, NomBytes};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::ops::Range;
+use nom::error::{ContextError, ErrorKind, FromExternalError, ParseError};
+use nom::ErrorConvert;
+
+type Source = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// An error for `nom` parsers over [`NomBytes`] that records where in the
+/// input it happened rather than a copy of the input itself.
+///
+/// `nom::error::Error<NomBytes>` keeps a clone of the remaining input around,
+/// which keeps the whole buffer alive for as long as the error is. This
+/// error only keeps positions, the [`ErrorKind`], a
+/// stack of contexts added with `nom::error::context` and, if the error came
+/// from [`FromExternalError`], the external error as its
+/// [`source`](std::error::Error::source). That makes it `'static`, `Send`
+/// and `Sync`, so it can be wrapped in `anyhow::Error` or sent to another
+/// thread.
+///
+/// # Examples
+///
+/// ```
+/// use nom::bytes::complete::tag;
+/// use nom::error::context;
+/// use nom::sequence::preceded;
+/// use nom::IResult;
+/// use nombytes::error::Error;
+/// use nombytes::NomBytes;
+///
+/// fn greeting(input: NomBytes) -> IResult<NomBytes, NomBytes, Error> {
+///     context("greeting", preceded(tag("hello "), tag("world")))(input)
+/// }
+///
+/// let input = NomBytes::from("hello there");
+/// let err = match greeting(input.clone()) {
+///     Err(nom::Err::Error(err)) => err,
+///     _ => unreachable!(),
+/// };
+///
+/// assert_eq!(err.offset_in(&input), Some(6));
+/// assert_eq!(err.context_in(&input).collect::<Vec<_>>(), [("greeting", 0..6)]);
+/// assert_eq!(err.to_string(), "Tag at greeting");
+/// ```
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    location: Location,
+    // Innermost first, which is the order `nom` adds them in.
+    context: Vec<(Location, &'static str)>,
+    source: Option<Source>,
+}
+
+/// Where an error happened: the [`span`](NomBytes::span) of the input in its
+/// origin, and the address and length of that origin, compared the same way
+/// [`same_origin`](NomBytes::same_origin) compares them. Keeping the origin
+/// itself would keep the whole buffer alive, so once it's freed, another
+/// buffer that happens to reuse its memory looks like the same origin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Location {
+    origin: (usize, usize),
+    span: Range<usize>,
+}
+
+impl Location {
+    fn of(input: &NomBytes) -> Self {
+        Self {
+            origin: origin_of(input),
+            span: input.span(),
+        }
+    }
+
+    /// Returns the span of `input` this location covers, if it's in `input`.
+    fn span_in(&self, input: &NomBytes) -> Option<Range<usize>> {
+        if origin_of(input) != self.origin {
+            return None;
+        }
+
+        let base = input.span();
+        if self.span.start < base.start || self.span.start > base.end {
+            return None;
+        }
+        Some(self.span.start - base.start..self.span.end.min(base.end) - base.start)
+    }
+}
+
+fn origin_of(input: &NomBytes) -> (usize, usize) {
+    let origin = input.origin();
+    let origin = origin.as_bytes();
+    (origin.as_ptr() as usize, origin.len())
+}
+
+impl Error {
+    /// Creates a new `Error` of the given kind at the start of `input`.
+    #[inline]
+    pub fn new(input: &NomBytes, kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            location: Location::of(input),
+            context: Vec::new(),
+            source: None,
+        }
+    }
+
+    /// Returns the kind of error the parser returned.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the offset in `origin` the error happened at, or `None` if
+    /// the error didn't happen in `origin` or any slice of it.
+    ///
+    /// The error doesn't keep the buffer it happened in alive, so it
+    /// recognizes `origin` by the address and length of its bytes. If that
+    /// buffer has been dropped, an unrelated `origin` that reuses its memory
+    /// can't be told apart from it, so only pass an `origin` that was alive
+    /// when the error was returned, like the input given to the parser.
+    #[inline]
+    pub fn offset_in(&self, origin: &NomBytes) -> Option<usize> {
+        self.span_in(origin).map(|span| span.start)
+    }
+
+    /// Returns the span of `origin` the failing parser was given, from where
+    /// the error happened to the end of that parser's input, or `None` if
+    /// the error didn't happen in `origin` or any slice of it.
+    ///
+    /// `origin` is recognized the same way as in [`offset_in`](Self::offset_in),
+    /// with the same caveat.
+    #[inline]
+    pub fn span_in(&self, origin: &NomBytes) -> Option<Range<usize>> {
+        self.location.span_in(origin)
+    }
+
+    /// Returns the contexts that were added to the error, innermost first.
+    pub fn context(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.context.iter().map(|(_, context)| *context)
+    }
+
+    /// Returns the contexts that were added to the error, innermost first,
+    /// together with the span of `origin` from where the context was entered
+    /// to where the error happened.
+    ///
+    /// Contexts that weren't entered in `origin` are left out. `origin` is
+    /// recognized the same way as in [`offset_in`](Self::offset_in).
+    pub fn context_in<'a>(
+        &'a self,
+        origin: &'a NomBytes,
+    ) -> impl Iterator<Item = (&'static str, Range<usize>)> + 'a {
+        let end = self.offset_in(origin);
+        self.context.iter().filter_map(move |(location, context)| {
+            let start = location.span_in(origin)?.start;
+            Some((*context, start..end?.max(start)))
+        })
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.kind.description())?;
+        for context in self.context() {
+            write!(f, " at {context}")?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, ": {source}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.source {
+            Some(source) => Some(&**source),
+            None => None,
+        }
+    }
+}
+
+impl ParseError<NomBytes> for Error {
+    #[inline]
+    fn from_error_kind(input: NomBytes, kind: ErrorKind) -> Self {
+        Self::new(&input, kind)
+    }
+
+    #[inline]
+    fn append(_input: NomBytes, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl ContextError<NomBytes> for Error {
+    #[inline]
+    fn add_context(input: NomBytes, ctx: &'static str, mut other: Self) -> Self {
+        other.context.push((Location::of(&input), ctx));
+        other
+    }
+}
+
+impl<E> FromExternalError<NomBytes, E> for Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[inline]
+    fn from_external_error(input: NomBytes, kind: ErrorKind, e: E) -> Self {
+        let mut error = Self::new(&input, kind);
+        error.source = Some(Box::new(e));
+        error
+    }
+}
+
+// The bit-level parsers in `nom::bits` run on `(input, bit offset)` and then
+// convert their errors into the byte-level error with `ErrorConvert`. Since
+// we only track bytes, the bit offset is dropped.
+
+impl ParseError<(NomBytes, usize)> for Error {
+    #[inline]
+    fn from_error_kind((input, _): (NomBytes, usize), kind: ErrorKind) -> Self {
+        Self::new(&input, kind)
+    }
+
+    #[inline]
+    fn append(_input: (NomBytes, usize), _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl ErrorConvert<Error> for Error {
+    #[inline]
+    fn convert(self) -> Error {
+        self
+    }
+}
+
+// The same goes for `nombytes::bits`, which has its own `ErrorConvert`.
+
+impl ParseError<NomBits> for Error {
+    #[inline]
+    fn from_error_kind(input: NomBits, kind: ErrorKind) -> Self {
+        Self::new(input.as_nom_bytes(), kind)
+    }
+
+    #[inline]
+    fn append(_input: NomBits, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl crate::bits::ErrorConvert<Error> for Error {
+    #[inline]
+    fn convert(self) -> Error {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use crate::NomBytes;
+    use nom::bits::bits;
+    use nom::bits::complete::take as take_bits;
+    use nom::bytes::complete::{tag, take_while1};
+    use nom::character::complete::digit1;
+    use nom::combinator::map_res;
+    use nom::error::{context, ErrorKind};
+    use nom::sequence::{preceded, tuple};
+    use nom::{IResult, Slice};
+
+    fn unwrap_error<O: core::fmt::Debug>(result: IResult<NomBytes, O, Error>) -> Error {
+        match result {
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e,
+            other => panic!("expected an error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn is_static_send_and_sync() {
+        fn assert_bounds<T: std::error::Error + Send + Sync + 'static>() {}
+        assert_bounds::<Error>();
+    }
+
+    #[test]
+    fn records_offsets_and_context() {
+        fn assignment(input: NomBytes) -> IResult<NomBytes, NomBytes, Error> {
+            context(
+                "assignment",
+                preceded(
+                    tuple((take_while1(|b: u8| b.is_ascii_alphabetic()), tag(" = "))),
+                    context("value", digit1),
+                ),
+            )(input)
+        }
+
+        let input = NomBytes::from("let x = y;");
+        let err = unwrap_error(preceded(tag("let "), assignment)(input.clone()));
+
+        assert_eq!(err.kind(), ErrorKind::Digit);
+        assert_eq!(err.offset_in(&input), Some(8));
+        assert_eq!(err.span_in(&input), Some(8..10));
+        assert_eq!(
+            err.context_in(&input).collect::<Vec<_>>(),
+            [("value", 8..8), ("assignment", 4..8)]
+        );
+
+        // Slices of the input resolve relative to themselves
+        assert_eq!(err.span_in(&input.slice(4..9)), Some(4..5));
+        assert_eq!(
+            err.context_in(&input.slice(4..)).collect::<Vec<_>>(),
+            [("value", 4..4), ("assignment", 0..4)]
+        );
+        assert_eq!(err.offset_in(&input.slice(..8)), Some(8));
+        assert_eq!(err.offset_in(&input.slice(..7)), None);
+        assert_eq!(err.to_string(), "Digit at value at assignment");
+
+        // Positions in some other buffer can't be resolved
+        let other = NomBytes::from("let x = y;");
+        assert_eq!(err.offset_in(&other), None);
+        assert_eq!(err.context_in(&other).count(), 0);
+    }
+
+    #[test]
+    fn keeps_external_errors_as_source() {
+        fn number(input: NomBytes) -> IResult<NomBytes, u8, Error> {
+            map_res(digit1, |digits: NomBytes| digits.to_str().parse::<u8>())(input)
+        }
+
+        let input = NomBytes::from("256");
+        let err = unwrap_error(number(input.clone()));
+
+        assert_eq!(err.kind(), ErrorKind::MapRes);
+        assert_eq!(err.offset_in(&input), Some(0));
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.is::<core::num::ParseIntError>());
+        assert_eq!(
+            err.to_string(),
+            "Map on Result: number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn converts_from_bit_errors() {
+        fn nibbles(input: NomBytes) -> IResult<NomBytes, (u8, u8), Error> {
+            bits::<_, _, Error, _, _>(tuple((take_bits(4usize), take_bits(4usize))))(input)
+        }
+
+        let input = NomBytes::from(&b"\xab"[..]);
+        assert_eq!(nibbles(input).unwrap().1, (0xa, 0xb));
+
+        let input = NomBytes::from(&b""[..]);
+        let err = unwrap_error(nibbles(input.clone()));
+        assert_eq!(err.kind(), ErrorKind::Eof);
+        assert_eq!(err.offset_in(&input), Some(0));
+    }
+
+    #[test]
+    fn converts_from_nom_bits_errors() {
+        fn flags(input: NomBytes) -> IResult<NomBytes, (bool, u16), Error> {
+            crate::bits::bits::<_, _, Error, _, _>(tuple((
+                crate::bits::complete::bool,
+                crate::bits::complete::take(12usize),
+            )))(input)
+        }
+
+        let input = NomBytes::from(&b"\x80\x10\x02"[..]);
+        assert_eq!(
+            flags(input).unwrap(),
+            (NomBytes::from(&b"\x02"[..]), (true, 2))
+        );
+
+        let input = NomBytes::from(&b"ab\x80"[..]);
+        let err = unwrap_error(flags(input.slice(2..)));
+        assert_eq!(err.kind(), ErrorKind::Eof);
+        assert_eq!(err.offset_in(&input), Some(2));
+    }
+}
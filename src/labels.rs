@@ -0,0 +1,99 @@
+//! Turning `nom` errors into labelled spans of the input they happened in,
+//! shared between the error reporting integrations.
+
+use crate::NomBytes;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use nom::error::{VerboseError, VerboseErrorKind};
+use nom::Needed;
+
+/// The message for errors and failures.
+pub(crate) const FAILED: &str = "failed to parse input";
+/// The message for running out of input.
+pub(crate) const INCOMPLETE: &str = "unexpected end of input";
+
+/// A `nom` error that knows where in its input it happened, which the error
+/// reporting integrations turn into labels on the source code: a
+/// [`NomDiagnostic`](crate::NomDiagnostic) with the `miette` feature, and
+/// [`NomSource::build_report`](crate::NomSource::build_report) with the
+/// `ariadne` feature.
+///
+/// This is implemented for `nom::error::Error<NomBytes>`,
+/// `nom::error::VerboseError<NomBytes>` and [`crate::error::Error`], and
+/// can be implemented for your own error types too.
+pub trait Labels {
+    /// Returns the byte ranges of `source` to label, and what to label them
+    /// with. `source` is the input the failing parser was originally given.
+    ///
+    /// Anything that didn't happen in `source` shouldn't be labelled, since
+    /// there's nowhere in it to put the label.
+    fn labels(&self, source: &NomBytes) -> Vec<(Range<usize>, String)>;
+}
+
+/// The label for an `Incomplete` result, which sits at the end of `source`.
+pub(crate) fn incomplete(needed: Needed, source: &NomBytes) -> (Range<usize>, String) {
+    let label = match needed {
+        Needed::Size(size) => format!("expected {size} more bytes"),
+        Needed::Unknown => "expected more input".to_owned(),
+    };
+    let end = source.as_bytes().len();
+    (end..end, label)
+}
+
+impl Labels for nom::error::Error<NomBytes> {
+    fn labels(&self, source: &NomBytes) -> Vec<(Range<usize>, String)> {
+        match source.try_offset(&self.input) {
+            Some(offset) => vec![(offset..offset, self.code.description().to_owned())],
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Labels for VerboseError<NomBytes> {
+    fn labels(&self, source: &NomBytes) -> Vec<(Range<usize>, String)> {
+        // The first entry is where the error happened, the rest are
+        // contexts and errors the parsers it happened in appended.
+        let error_offset = self
+            .errors
+            .first()
+            .and_then(|(input, _)| source.try_offset(input));
+
+        self.errors
+            .iter()
+            .filter_map(|(input, kind)| {
+                let offset = source.try_offset(input)?;
+                let label = match kind {
+                    VerboseErrorKind::Context(context) => {
+                        let end = error_offset.map_or(offset, |end| end.max(offset));
+                        return Some((offset..end, format!("while parsing {context}")));
+                    }
+                    VerboseErrorKind::Char(c) => format!("expected '{c}'"),
+                    VerboseErrorKind::Nom(kind) => kind.description().to_owned(),
+                };
+                Some((offset..offset, label))
+            })
+            .collect()
+    }
+}
+
+impl Labels for crate::error::Error {
+    fn labels(&self, source: &NomBytes) -> Vec<(Range<usize>, String)> {
+        let label = match std::error::Error::source(self) {
+            Some(e) => format!("{}: {e}", self.kind().description()),
+            None => self.kind().description().to_owned(),
+        };
+        let mut labels = Vec::new();
+        if let Some(offset) = self.offset_in(source) {
+            labels.push((offset..offset, label));
+        }
+        labels.extend(
+            self.context_in(source)
+                .map(|(context, span)| (span, format!("while parsing {context}"))),
+        );
+        labels
+    }
+}
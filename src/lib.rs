@@ -2,7 +2,12 @@
 //
 #![cfg_attr(docsrs, feature(doc_cfg))]
 //
-#![doc = include_str!("../README.md")]
+// The README example is written against nom 6/7
+#![cfg_attr(feature = "nom7", doc = include_str!("../README.md"))]
+#![cfg_attr(
+    not(feature = "nom7"),
+    doc = "A wrapper for the `bytes::Bytes` byte container for use with nom."
+)]
 //
 #![deny(anonymous_parameters)]
 #![deny(nonstandard_style)]
@@ -21,48 +26,243 @@
 //
 // Clippy
 #![warn(clippy::pedantic)]
+// Trips on the crate name in the README heading
+#![allow(clippy::doc_markdown)]
+
+extern crate alloc;
 
 use bytes::Bytes;
 use core::fmt::Display;
-use core::iter::Enumerate;
-use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
+use core::ops::{Bound, Range, RangeBounds};
 use core::str::Utf8Error;
-use nom::{
-    AsBytes, Compare, FindSubstring, InputIter, InputLength, InputTake, InputTakeAtPosition,
-    Needed, Offset, Slice,
-};
 
 mod range_type;
 pub use range_type::RangeType;
 
+mod backing;
+pub use backing::Backing;
+
+mod nom_bytes_ref;
+pub use nom_bytes_ref::NomBytesRef;
+
+mod nom_str;
+pub use nom_str::{NomStr, NomStrCharIndices, NomStrChars};
+
+mod located;
+pub use located::LocatedNomBytes;
+
+mod chain;
+pub use chain::{NomChain, NomChainIter};
+
+mod tokens;
+pub use tokens::{NomTokens, NomTokensIter};
+
+mod line_index;
+pub use line_index::{LineCol, LineIndex};
+
+mod source_map;
+pub use source_map::{SourceFile, SourceLocation, SourceMap};
+
+#[cfg(feature = "nom7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "nom7")))]
+mod nom7;
+
+#[cfg(feature = "nom8")]
+#[cfg_attr(docsrs, doc(cfg(feature = "nom8")))]
+mod nom8;
+
+#[cfg(feature = "winnow")]
+#[cfg_attr(docsrs, doc(cfg(feature = "winnow")))]
+mod winnow;
+
 #[cfg(feature = "miette")]
 #[cfg_attr(docsrs, doc(cfg(feature = "miette")))]
 mod miette;
+#[cfg(all(feature = "miette", feature = "nom7"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "miette", feature = "nom7"))))]
+pub use crate::miette::{into_report, NomDiagnostic};
+
+#[cfg(feature = "ariadne")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ariadne")))]
+mod ariadne;
+#[cfg(feature = "ariadne")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ariadne")))]
+pub use crate::ariadne::NomSource;
+
+#[cfg(feature = "codespan")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codespan")))]
+mod codespan;
+#[cfg(feature = "codespan")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codespan")))]
+pub use crate::codespan::NomFiles;
+
+#[cfg(all(feature = "nom7", any(feature = "miette", feature = "ariadne")))]
+mod labels;
+#[cfg(all(feature = "nom7", any(feature = "miette", feature = "ariadne")))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "nom7", any(feature = "miette", feature = "ariadne"))))
+)]
+pub use crate::labels::Labels;
+
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+mod codec;
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+pub use codec::{NomDecoder, NomDecoderError};
+
+#[cfg(feature = "nom7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "nom7")))]
+pub mod bits;
+#[cfg(feature = "nom7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "nom7")))]
+pub use bits::NomBits;
+
+#[cfg(all(feature = "std", feature = "nom7"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "nom7"))))]
+pub mod error;
 
-/// A wrapper around [`bytes::Bytes`] to be able to use it with [`nom`].
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct NomBytes(Bytes, Option<RangeType<usize>>);
+#[cfg(all(feature = "std", feature = "nom7"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "nom7"))))]
+mod stream;
+#[cfg(all(feature = "std", feature = "nom7"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "nom7"))))]
+pub use stream::{StreamParser, StreamParserError};
 
-// Why the extra `Option<RangeType<usize>>`? Nom expects to be able to calculate
-// offsets between two of its inputs, but `Bytes` has this optimization where if
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+mod async_stream;
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+pub use async_stream::{AsyncStreamParser, StreamChunk};
+
+mod buf;
+
+#[cfg(feature = "mmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+mod mmap;
+
+#[cfg(feature = "lsp")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lsp")))]
+mod lsp;
+
+/// A wrapper around [`bytes::Bytes`] to be able to use it with `nom`.
+///
+/// Every `NomBytes` remembers the [`Bytes`] it was originally created from,
+/// its *origin*, and where in it it is. Slicing one only narrows that range,
+/// so the position of a parsed value is available from the value itself
+/// through [`span`](GenericNomBytes::span), long after the parser that
+/// produced it is gone.
+///
+/// This is the [`Bytes`]-backed [`GenericNomBytes`], which is what the rest
+/// of this crate is built on.
+pub type NomBytes = GenericNomBytes<Bytes>;
+
+/// The type behind [`NomBytes`], generic over the [`Backing`] storage it is
+/// a slice of.
+///
+/// Everything that only needs the bytes themselves, including the `nom` and
+/// `winnow` impls, works with any backing, so a single-threaded tool can use
+/// `Rc<[u8]>` to avoid atomics, and an embedded one `&'static [u8]` to avoid
+/// allocating. Conversions to and from [`Bytes`], and the types built on top
+/// of `NomBytes`, are only available for `NomBytes`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "nom7")]
+/// # {
+/// use nom::bytes::complete::tag;
+/// use nombytes::GenericNomBytes;
+///
+/// static INPUT: &[u8] = b"hello, world";
+///
+/// let input = GenericNomBytes::new(INPUT);
+/// let (rest, _) = tag::<_, _, ()>("hello, ")(input).unwrap();
+///
+/// assert_eq!(rest.as_bytes(), b"world");
+/// assert_eq!(rest.span(), 7..12);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GenericNomBytes<B> {
+    origin: B,
+    start: usize,
+    end: usize,
+}
+
+// Why not just slice the `Bytes` (or whatever the `Backing` is)? Nom expects to be able to calculate offsets
+// between two of its inputs, but `Bytes` has this optimization where if
 // slicing results in an empty slice, it returns a new, empty `Bytes` rather than
 // an empty slice of the existing `Bytes`. This causes problems down the line when
-// nom asks for offsets between two inputs. Thus, in cases where slicing would
-// result in an empty slice, we instead store the original `Bytes` plus the slice
-// range itself, which we can use to hand out correct offsets.
+// nom asks for offsets between two inputs. We also want every value to know
+// where in the input it came from. Thus, we always store the original `Bytes`
+// plus the range of it we cover, which we can use to hand out correct offsets.
+// Keeping that range as two plain offsets also makes slicing, which parsers do
+// a lot of, nothing but integer arithmetic and a single reference count bump.
+// `start <= end <= origin.len()` always holds.
 //
-// All the code here uses `bytes()` or `as_bytes()` for doing operations on the
-// underlying bytes rather than accessing the "raw" `.0` field, because those two
-// contain code that handles this custom slicing correctly, and thus we don't have
-// to be careful anywhere else.
+// All the code here uses `to_backing()` or `as_bytes()` for doing operations
+// on the underlying bytes rather than accessing the `origin` field directly,
+// because those two contain code that handles this custom slicing correctly,
+// and thus we don't have to be careful anywhere else.
 //
 // Tried reporting this as unexpected/incorrect behavior, but it was said to be an
 // intentional behavior:
 // <https://github.com/tokio-rs/bytes/issues/557>
 
-impl NomBytes {
-    /// Creates a new `NomBytes` wrapping the provided [`Bytes`].
+// Serialized values only hold on to their own contents rather than all of the
+// origin. They keep the format `NomBytes` was serialized with before it
+// tracked origins, which is why there's an optional range in there.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RawNomBytes(Bytes, Option<RangeType<usize>>);
+
+#[cfg(feature = "serde")]
+impl From<NomBytes> for RawNomBytes {
+    fn from(nb: NomBytes) -> Self {
+        Self(nb.into_bytes(), None)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawNomBytes> for NomBytes {
+    type Error = &'static str;
+
+    fn try_from(RawNomBytes(bytes, range_type): RawNomBytes) -> Result<Self, Self::Error> {
+        let range = match range_type {
+            Some(range_type) => bounds(&range_type, bytes.len()),
+            None => 0..bytes.len(),
+        };
+        if range.start <= range.end && range.end <= bytes.len() {
+            Ok(Self::from_raw((bytes, range)))
+        } else {
+            Err("range is out of bounds for its bytes")
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl serde::Serialize for NomBytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawNomBytes::from(self.clone()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for NomBytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawNomBytes::deserialize(deserializer)?;
+        Self::try_from(raw).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<B: Backing> GenericNomBytes<B> {
+    /// Creates a new `NomBytes` wrapping the provided [`Bytes`], or other
+    /// [`Backing`].
     ///
     /// # Examples
     ///
@@ -74,8 +274,12 @@ impl NomBytes {
     /// let nb = NomBytes::new(b);
     /// ```
     #[inline]
-    pub fn new(bytes: Bytes) -> Self {
-        Self(bytes, None)
+    pub fn new(backing: B) -> Self {
+        Self {
+            start: 0,
+            end: backing.as_slice().len(),
+            origin: backing,
+        }
     }
 
     /// Returns a string slice to the contents of the inner [`Bytes`].
@@ -119,7 +323,11 @@ impl NomBytes {
         core::str::from_utf8(self.as_bytes())
     }
 
-    #[doc = include_str!("to_bytes_doc.md")]
+    /// Returns a byte slice to the contents of the inner [`Bytes`].
+    ///
+    /// This is also what the `AsBytes` impls for the supported `nom`
+    /// versions hand out.
+    ///
     /// # Examples
     ///
     /// ```
@@ -127,37 +335,47 @@ impl NomBytes {
     /// use nombytes::NomBytes;
     ///
     /// let nb = NomBytes::new(Bytes::from("hello"));
-    /// let b = nb.to_bytes();
-    /// assert_eq!(b.as_ref(), b"hello");
+    /// assert_eq!(nb.as_bytes(), b"hello");
     /// ```
     #[inline]
-    pub fn to_bytes(&self) -> Bytes {
-        match self.1.as_ref() {
-            Some(range) => self.0.slice(range.clone()),
-            None => self.0.clone(),
-        }
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.origin.as_slice()[self.start..self.end]
     }
 
-    #[doc = include_str!("to_bytes_doc.md")]
+    /// Returns the contents as a [`Backing`] of their own, which, depending
+    /// on the backing, may be a copy. For [`Bytes`], this is
+    /// [`to_bytes`](NomBytes::to_bytes).
+    ///
     /// # Examples
     ///
     /// ```
-    /// use bytes::Bytes;
-    /// use nombytes::NomBytes;
+    /// use std::rc::Rc;
+    /// use nombytes::GenericNomBytes;
     ///
-    /// let nb = NomBytes::new(Bytes::from("hello"));
-    /// let b = nb.into_bytes();
-    /// assert_eq!(b.as_ref(), b"hello");
+    /// let nb = GenericNomBytes::new(Rc::<[u8]>::from(&b"hello"[..]));
+    /// assert_eq!(&*nb.to_backing(), b"hello");
     /// ```
     #[inline]
-    pub fn into_bytes(self) -> Bytes {
-        match self.1.as_ref() {
-            Some(range) => self.0.slice(range.clone()),
-            None => self.0,
+    #[must_use]
+    pub fn to_backing(&self) -> B {
+        self.origin.slice(self.start..self.end)
+    }
+
+    /// Like [`to_backing`](Self::to_backing), but hands out the origin
+    /// itself, rather than a slice or copy of it, if this covers all of it.
+    /// For [`Bytes`], this is [`into_bytes`](NomBytes::into_bytes).
+    #[inline]
+    #[must_use]
+    pub fn into_backing(self) -> B {
+        if self.start == 0 && self.end == self.origin.as_slice().len() {
+            self.origin
+        } else {
+            self.to_backing()
         }
     }
 
-    /// Returns the values from the inner representation of this type.
+    /// Returns the values from the inner representation of this type, which
+    /// are the origin [`Bytes`] and the range of it this value covers.
     ///
     /// See [`into_bytes`](Self::into_bytes) for an explanation of why this
     /// inner representation exists.
@@ -168,21 +386,19 @@ impl NomBytes {
     /// use nombytes::NomBytes;
     ///
     /// let nb = NomBytes::new(Bytes::from("hello"));
-    /// let (b, rt) = nb.into_raw();
+    /// let (b, range) = nb.into_raw();
     /// assert_eq!(b.as_ref(), b"hello");
-    /// assert!(rt.is_none());
+    /// assert_eq!(range, 0..5);
     /// ```
     // I dunno what anyone would use this for, but... might as well
     // offer it.
-    pub fn into_raw(self) -> (Bytes, Option<RangeType<usize>>) {
-        let Self(bytes, range_type) = self;
-        (bytes, range_type)
+    pub fn into_raw(self) -> (B, Range<usize>) {
+        (self.origin, self.start..self.end)
     }
 
-    /// Returns a new `NomBytes` using the raw values passed in. If these
-    /// values represent something invalid, you'll likely see incorrect
-    /// behavior or even panics. Regular usage should create values using
-    /// [`new`](Self::new) instead.
+    /// Returns a new `NomBytes` using the raw values passed in, the origin
+    /// [`Bytes`], or other [`Backing`], and the range of it the value covers.
+    /// Regular usage should create values using [`new`](Self::new) instead.
     ///
     /// See [`into_bytes`](Self::into_bytes) for an explanation of why this
     /// inner representation exists.
@@ -190,246 +406,393 @@ impl NomBytes {
     ///
     /// ```
     /// use bytes::Bytes;
-    /// use nom::AsBytes;
-    /// use nombytes::{NomBytes, RangeType};
+    /// use nombytes::NomBytes;
     ///
     /// let b = Bytes::from("hello");
-    /// let rt = Some(RangeType::RangeTo(..3));
-    /// let nb = NomBytes::from_raw((b, rt));
+    /// let nb = NomBytes::from_raw((b, 0..3));
     /// assert_eq!(nb.as_bytes(), b"hel");
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds for `bytes`.
     // I dunno what anyone would use this for, but... might as well
     // offer it.
-    pub fn from_raw((bytes, range_type): (Bytes, Option<RangeType<usize>>)) -> Self {
-        Self(bytes, range_type)
-    }
-}
-
-impl AsBytes for NomBytes {
-    #[inline]
-    fn as_bytes(&self) -> &[u8] {
-        match self.1.as_ref() {
-            Some(range) => range.slice(self.0.as_ref()),
-            None => self.0.as_ref(),
+    pub fn from_raw((bytes, range): (B, Range<usize>)) -> Self {
+        let len = bytes.as_slice().len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {range:?} is out of bounds for bytes of length {len}"
+        );
+        Self {
+            origin: bytes,
+            start: range.start,
+            end: range.end,
         }
     }
-}
-
-impl InputIter for NomBytes {
-    type Item = u8;
-    type Iter = Enumerate<Self::IterElem>;
-    type IterElem = bytes::buf::IntoIter<Bytes>;
 
+    /// Returns the [`Bytes`] this value was originally created from, or
+    /// sliced out of, as a `NomBytes` of its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "nom7")]
+    /// # {
+    /// use nom::bytes::complete::tag;
+    /// use nom::IResult;
+    /// use nombytes::NomBytes;
+    ///
+    /// let input = NomBytes::from("hello, world");
+    /// let (_, hello): (_, NomBytes) = tag::<_, _, ()>("hello")(input).unwrap();
+    ///
+    /// assert_eq!(hello.origin().as_bytes(), b"hello, world");
+    /// # }
+    /// ```
     #[inline]
-    fn iter_indices(&self) -> Self::Iter {
-        self.iter_elements().enumerate()
+    #[must_use]
+    pub fn origin(&self) -> Self {
+        Self::new(self.origin.clone())
     }
 
+    /// Returns the range of its [`origin`](Self::origin) this value covers.
+    ///
+    /// Since this is part of the value itself, it keeps working after the
+    /// value has been stored away, e.g. in an AST, and the input it was
+    /// parsed from is long gone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "nom7")]
+    /// # {
+    /// use nom::bytes::complete::{tag, take_until};
+    /// use nom::sequence::preceded;
+    /// use nom::IResult;
+    /// use nombytes::NomBytes;
+    ///
+    /// fn value(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+    ///     preceded(tag("key = "), take_until(";"))(input)
+    /// }
+    ///
+    /// let (_, value) = value(NomBytes::from("key = value;")).unwrap();
+    /// assert_eq!(value.span(), 6..11);
+    /// # }
+    /// ```
     #[inline]
-    fn iter_elements(&self) -> Self::IterElem {
-        self.to_bytes().into_iter()
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
     }
 
+    /// Returns the offset of the start of this value in its
+    /// [`origin`](Self::origin). This is the same as `self.span().start`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomBytes;
+    ///
+    /// let nb = NomBytes::from_raw(("hello".into(), 2..5));
+    /// assert_eq!(nb.offset_in_origin(), 2);
+    /// ```
     #[inline]
-    fn position<P>(&self, predicate: P) -> Option<usize>
-    where
-        P: Fn(Self::Item) -> bool,
-    {
-        self.as_bytes().iter().position(|b| predicate(*b))
+    #[must_use]
+    pub fn offset_in_origin(&self) -> usize {
+        self.start
     }
 
+    /// Slices this `NomBytes`, keeping track of the original [`Bytes`]. This
+    /// is the implementation behind the `Slice` and `Input` impls for the
+    /// supported `nom` versions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, like slicing a `&[u8]` would.
     #[inline]
-    fn slice_index(&self, count: usize) -> Result<usize, nom::Needed> {
-        if self.as_bytes().len() >= count {
-            Ok(count)
-        } else {
-            Err(Needed::new(count - self.as_bytes().len()))
+    pub(crate) fn slice_range<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let len = self.end - self.start;
+        let range = bounds(&range, len);
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {range:?} is out of bounds for a slice of length {len}"
+        );
+
+        Self {
+            origin: self.origin.clone(),
+            start: self.start + range.start,
+            end: self.start + range.end,
         }
     }
 }
 
-impl InputTake for NomBytes {
+impl NomBytes {
+    #[doc = include_str!("to_bytes_doc.md")]
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use nombytes::NomBytes;
+    ///
+    /// let nb = NomBytes::new(Bytes::from("hello"));
+    /// let b = nb.to_bytes();
+    /// assert_eq!(b.as_ref(), b"hello");
+    /// ```
     #[inline]
-    fn take(&self, count: usize) -> Self {
-        self.slice(..count)
+    pub fn to_bytes(&self) -> Bytes {
+        self.origin.slice(self.start..self.end)
     }
 
+    #[doc = include_str!("to_bytes_doc.md")]
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use nombytes::NomBytes;
+    ///
+    /// let nb = NomBytes::new(Bytes::from("hello"));
+    /// let b = nb.into_bytes();
+    /// assert_eq!(b.as_ref(), b"hello");
+    /// ```
     #[inline]
-    fn take_split(&self, count: usize) -> (Self, Self) {
-        let prefix = self.slice(..count);
-        let suffix = self.slice(count..);
-        (suffix, prefix)
-    }
-}
-
-impl InputTakeAtPosition for NomBytes {
-    type Item = <Self as InputIter>::Item;
-
-    fn split_at_position<P, E: nom::error::ParseError<Self>>(
-        &self,
-        predicate: P,
-    ) -> nom::IResult<Self, Self, E>
-    where
-        P: Fn(Self::Item) -> bool,
-    {
-        match self.as_bytes().iter().position(|c| predicate(*c)) {
-            Some(i) => Ok(self.take_split(i)),
-            None => Err(nom::Err::Incomplete(Needed::new(1))),
-        }
-    }
-
-    fn split_at_position1<P, E: nom::error::ParseError<Self>>(
-        &self,
-        predicate: P,
-        e: nom::error::ErrorKind,
-    ) -> nom::IResult<Self, Self, E>
-    where
-        P: Fn(Self::Item) -> bool,
-    {
-        match self.as_bytes().iter().position(|c| predicate(*c)) {
-            Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
-            Some(i) => Ok(self.take_split(i)),
-            None => Err(nom::Err::Incomplete(Needed::new(1))),
-        }
-    }
-
-    fn split_at_position_complete<P, E: nom::error::ParseError<Self>>(
-        &self,
-        predicate: P,
-    ) -> nom::IResult<Self, Self, E>
-    where
-        P: Fn(Self::Item) -> bool,
-    {
-        match self.as_bytes().iter().position(|c| predicate(*c)) {
-            Some(i) => Ok(self.take_split(i)),
-            None => Ok(self.take_split(self.input_len())),
+    pub fn into_bytes(self) -> Bytes {
+        if self.start == 0 && self.end == self.origin.len() {
+            self.origin
+        } else {
+            self.origin.slice(self.start..self.end)
         }
     }
 
-    fn split_at_position1_complete<P, E: nom::error::ParseError<Self>>(
-        &self,
-        predicate: P,
-        e: nom::error::ErrorKind,
-    ) -> nom::IResult<Self, Self, E>
-    where
-        P: Fn(Self::Item) -> bool,
-    {
-        let bytes = self.as_bytes();
-        match bytes.iter().position(|c| predicate(*c)) {
-            Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
-            Some(i) => Ok(self.take_split(i)),
-            None => {
-                if bytes.is_empty() {
-                    Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
-                } else {
-                    Ok(self.take_split(self.input_len()))
-                }
-            }
-        }
+    /// Reads the whole file at `path` into a new `NomBytes`.
+    ///
+    /// With the `mmap` feature, [`open_file`](Self::open_file) can map the
+    /// file into memory instead, which avoids reading it all up front. This
+    /// is the fallback for files that can't or shouldn't be mapped, like
+    /// pipes or files that may change while they're being parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nombytes::NomBytes;
+    ///
+    /// let log = NomBytes::read_file("server.log")?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file couldn't be read.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn read_file<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        std::fs::read(path).map(|contents| Self::new(Bytes::from(contents)))
     }
 }
 
-impl InputLength for NomBytes {
+impl<B: Backing> GenericNomBytes<B> {
+    /// Returns the byte offset of `second` relative to `self`, which must be
+    /// a slice of the same input starting at or after `self`. This is the
+    /// implementation behind the `Offset` impls for the supported `nom`
+    /// versions.
+    ///
+    /// With debug assertions enabled, e.g. in tests, this panics if `second`
+    /// isn't a slice of `self`, rather than returning garbage.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
     #[inline]
-    fn input_len(&self) -> usize {
-        self.as_bytes().len()
-    }
-}
-
-macro_rules! nom_bytes_slice {
-    ($range_ty:ty, $requirement:expr) => {
-        impl Slice<$range_ty> for NomBytes {
-            fn slice(&self, range: $range_ty) -> Self {
-                let bytes = self.to_bytes();
-                if bytes.is_empty() && $requirement(&range) {
-                    return self.clone();
-                }
-
-                let slice = bytes.slice(range.clone());
-                if slice.is_empty() {
-                    NomBytes(bytes, Some(RangeType::from(range)))
-                } else {
-                    assert!(!slice.is_empty());
-                    NomBytes(slice, None)
-                }
-            }
-        }
-    };
-}
+    pub(crate) fn offset_to(&self, second: &Self) -> usize {
+        debug_assert!(
+            self.try_offset(second).is_some(),
+            "offset of a NomBytes that isn't a slice of this one: {second:?} isn't in {self:?}"
+        );
+        let fst = self.as_bytes().as_ptr();
+        let snd = second.as_bytes().as_ptr();
 
-nom_bytes_slice!(Range<usize>, |r: &Range<usize>| r.start == 0 && r.end == 0);
-nom_bytes_slice!(RangeTo<usize>, |r: &RangeTo<usize>| r.end == 0);
-nom_bytes_slice!(RangeFrom<usize>, |r: &RangeFrom<usize>| r.start == 0);
-nom_bytes_slice!(RangeFull, |_: &RangeFull| true);
+        snd as usize - fst as usize
+    }
 
-impl Offset for NomBytes {
-    #[inline]
-    fn offset(&self, second: &Self) -> usize {
-        self.as_bytes().offset(second.as_bytes())
+    /// Returns the byte offset of `second` relative to `self`, or `None` if
+    /// `second` isn't a slice of `self`.
+    ///
+    /// This is the checked version of `nom`'s `Offset::offset`, which
+    /// assumes `second` is a slice of `self` and returns garbage, or panics,
+    /// if it isn't, such as when one of them has been through
+    /// [`to_bytes`](Self::to_bytes) and [`NomBytes::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomBytes;
+    ///
+    /// let input = NomBytes::from("hello, world");
+    /// let world = NomBytes::new(input.to_bytes().slice(7..));
+    /// assert_eq!(input.try_offset(&world), Some(7));
+    ///
+    /// // The same bytes, but not the same memory
+    /// let copy = NomBytes::from("world");
+    /// assert_eq!(input.try_offset(&copy), None);
+    /// // `input` isn't a slice of `world`
+    /// assert_eq!(world.try_offset(&input), None);
+    /// ```
+    #[must_use]
+    pub fn try_offset(&self, second: &Self) -> Option<usize> {
+        let fst = self.as_bytes().as_ptr() as usize;
+        let snd = second.as_bytes().as_ptr() as usize;
+
+        let offset = snd.checked_sub(fst)?;
+        (offset + second.as_bytes().len() <= self.as_bytes().len()).then(|| offset)
     }
-}
 
-impl Compare<NomBytes> for NomBytes {
+    /// Returns `true` if `self` and `other` have the same
+    /// [`origin`](Self::origin), i.e. they were sliced out of the very same
+    /// [`Bytes`], even if they don't overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomBytes;
+    ///
+    /// let input = NomBytes::from("hello, world");
+    /// let world = NomBytes::new(input.to_bytes().slice(7..));
+    /// assert!(input.origin().same_origin(&input));
+    /// // Sliced out of a new `Bytes`, so its origin is only the slice
+    /// assert!(!world.same_origin(&input));
+    /// ```
     #[inline]
-    fn compare(&self, t: NomBytes) -> nom::CompareResult {
-        self.as_bytes().compare(t.as_bytes())
+    #[must_use]
+    pub fn same_origin(&self, other: &Self) -> bool {
+        let (fst, snd) = (self.origin.as_slice(), other.origin.as_slice());
+        fst.as_ptr() == snd.as_ptr() && fst.len() == snd.len()
     }
 
+    /// Returns `true` if `self` and `other` are views of the very same
+    /// memory, rather than just equal bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomBytes;
+    ///
+    /// let input = NomBytes::from("hello");
+    /// assert!(input.ptr_eq(&input.clone()));
+    /// assert!(!input.ptr_eq(&NomBytes::from("hello")));
+    /// ```
     #[inline]
-    fn compare_no_case(&self, t: NomBytes) -> nom::CompareResult {
-        self.as_bytes().compare_no_case(t.as_bytes())
+    #[must_use]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        let (fst, snd) = (self.as_bytes(), other.as_bytes());
+        fst.as_ptr() == snd.as_ptr() && fst.len() == snd.len()
     }
 }
 
-impl Compare<&'_ [u8]> for NomBytes {
-    #[inline]
-    fn compare(&self, t: &[u8]) -> nom::CompareResult {
-        self.as_bytes().compare(t.as_bytes())
+/// Turns any range into a `Range`, given the length of what it is a range of.
+#[inline]
+fn bounds<R: RangeBounds<usize>>(range: &R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => end + 1,
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => len,
+    };
+    start..end
+}
+
+/// Formats bytes the way `Bytes` does, as a byte string literal.
+pub(crate) struct ByteStr<'a>(pub(crate) &'a [u8]);
+
+impl core::fmt::Debug for ByteStr<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "b\"")?;
+        for &b in self.0 {
+            match b {
+                b'\n' => write!(f, "\\n")?,
+                b'\r' => write!(f, "\\r")?,
+                b'\t' => write!(f, "\\t")?,
+                b'\\' | b'"' => write!(f, "\\{}", b as char)?,
+                b'\0' => write!(f, "\\0")?,
+                0x20..=0x7e => write!(f, "{}", b as char)?,
+                _ => write!(f, "\\x{b:02x}")?,
+            }
+        }
+        write!(f, "\"")
     }
+}
 
-    #[inline]
-    fn compare_no_case(&self, t: &[u8]) -> nom::CompareResult {
-        self.as_bytes().compare_no_case(t.as_bytes())
+// Only the contents and where they are in the origin, since the whole
+// origin could be very large.
+impl<B: Backing> core::fmt::Debug for GenericNomBytes<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("NomBytes")
+            .field(&ByteStr(self.as_bytes()))
+            .field(&self.span())
+            .finish()
     }
 }
 
-impl Compare<&'_ str> for NomBytes {
+/// An iterator over the bytes of a [`GenericNomBytes`], which is what the
+/// `nom` and `winnow` impls hand out.
+///
+/// It holds on to a clone of the value it iterates over, so it doesn't
+/// borrow it.
+#[derive(Clone)]
+pub struct NomBytesIter<B = Bytes>(GenericNomBytes<B>);
+
+impl<B: Backing> Iterator for NomBytesIter<B> {
+    type Item = u8;
+
     #[inline]
-    fn compare(&self, t: &str) -> nom::CompareResult {
-        self.as_bytes().compare(t.as_bytes())
+    fn next(&mut self) -> Option<u8> {
+        let b = *self.0.as_bytes().first()?;
+        self.0.start += 1;
+        Some(b)
     }
 
     #[inline]
-    fn compare_no_case(&self, t: &str) -> nom::CompareResult {
-        self.as_bytes().compare_no_case(t.as_bytes())
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.end - self.0.start;
+        (len, Some(len))
     }
 }
 
-impl FindSubstring<NomBytes> for NomBytes {
-    fn find_substring(&self, substr: NomBytes) -> Option<usize> {
-        self.as_bytes().find_substring(substr.as_bytes())
+impl<B: Backing> DoubleEndedIterator for NomBytesIter<B> {
+    #[inline]
+    fn next_back(&mut self) -> Option<u8> {
+        let b = *self.0.as_bytes().last()?;
+        self.0.end -= 1;
+        Some(b)
     }
 }
 
-impl FindSubstring<&'_ [u8]> for NomBytes {
-    fn find_substring(&self, substr: &[u8]) -> Option<usize> {
-        self.as_bytes().find_substring(substr)
+impl<B: Backing> ExactSizeIterator for NomBytesIter<B> {}
+
+impl<B: Backing> core::iter::FusedIterator for NomBytesIter<B> {}
+
+impl<B: Backing> core::fmt::Debug for NomBytesIter<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("NomBytesIter").field(&self.0).finish()
     }
 }
 
-impl FindSubstring<&'_ str> for NomBytes {
-    fn find_substring(&self, substr: &str) -> Option<usize> {
-        self.as_bytes().find_substring(substr)
+impl<B: Backing> IntoIterator for GenericNomBytes<B> {
+    type Item = u8;
+    type IntoIter = NomBytesIter<B>;
+
+    #[inline]
+    fn into_iter(self) -> NomBytesIter<B> {
+        NomBytesIter(self)
     }
 }
 
-impl Display for NomBytes {
+impl<B: Backing> Display for GenericNomBytes<B> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self.try_to_str() {
-            Ok(s) => write!(f, "{}", s),
+            Ok(s) => write!(f, "{s}"),
             Err(e) => {
-                write!(f, "<{}>", e)?;
+                write!(f, "<{e}>")?;
                 Err(core::fmt::Error)
             }
         }
@@ -463,42 +826,139 @@ impl From<String> for NomBytes {
     }
 }
 
-impl From<Bytes> for NomBytes {
+impl<B: Backing> From<B> for GenericNomBytes<B> {
     #[inline]
-    fn from(bytes: Bytes) -> Self {
-        Self::new(bytes)
+    fn from(backing: B) -> Self {
+        Self::new(backing)
     }
 }
 
 // We implement the eq/ord traits in terms of &[u8] since it's both
 // cheap and easy:
 
-impl PartialEq for NomBytes {
+impl<B: Backing> PartialEq for GenericNomBytes<B> {
     fn eq(&self, other: &Self) -> bool {
         self.as_bytes().eq(other.as_bytes())
     }
 }
-impl Eq for NomBytes {}
+impl<B: Backing> Eq for GenericNomBytes<B> {}
 
-impl PartialOrd for NomBytes {
+impl<B: Backing> PartialOrd for GenericNomBytes<B> {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        self.as_bytes().partial_cmp(other.as_bytes())
+        Some(self.cmp(other))
     }
 }
-impl Ord for NomBytes {
+impl<B: Backing> Ord for GenericNomBytes<B> {
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.as_bytes().cmp(other.as_bytes())
     }
 }
 
+// Hashing the bytes the same way `[u8]` does is what lets a `NomBytes` key
+// be looked up by a `&[u8]` through `Borrow`.
+impl<B: Backing> core::hash::Hash for GenericNomBytes<B> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl<B: Backing> core::borrow::Borrow<[u8]> for GenericNomBytes<B> {
+    #[inline]
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<B: Backing> AsRef<[u8]> for GenericNomBytes<B> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<B: Backing> core::ops::Deref for GenericNomBytes<B> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+// Comparisons with the other ways of holding bytes, in both directions, like
+// `Bytes` has.
+
+macro_rules! cmp_with {
+    ($($ty:ty),*) => {$(
+        impl<B: Backing> PartialEq<$ty> for GenericNomBytes<B> {
+            #[inline]
+            fn eq(&self, other: &$ty) -> bool {
+                self.as_bytes() == AsRef::<[u8]>::as_ref(other)
+            }
+        }
+
+        impl<B: Backing> PartialOrd<$ty> for GenericNomBytes<B> {
+            #[inline]
+            fn partial_cmp(&self, other: &$ty) -> Option<core::cmp::Ordering> {
+                self.as_bytes().partial_cmp(AsRef::<[u8]>::as_ref(other))
+            }
+        }
+    )*};
+}
+
+macro_rules! cmp_with_reversed {
+    ($($ty:ty),*) => {$(
+        impl<B: Backing> PartialEq<GenericNomBytes<B>> for $ty {
+            #[inline]
+            fn eq(&self, other: &GenericNomBytes<B>) -> bool {
+                other == self
+            }
+        }
+
+        impl<B: Backing> PartialOrd<GenericNomBytes<B>> for $ty {
+            #[inline]
+            fn partial_cmp(&self, other: &GenericNomBytes<B>) -> Option<core::cmp::Ordering> {
+                other.partial_cmp(self).map(core::cmp::Ordering::reverse)
+            }
+        }
+    )*};
+}
+
+cmp_with!([u8], str, Bytes, alloc::vec::Vec<u8>, alloc::string::String);
+cmp_with_reversed!([u8], str, Bytes, alloc::vec::Vec<u8>, alloc::string::String);
+// The other direction is covered by the impl for references below.
+cmp_with_reversed!(&[u8], &str);
+
+impl<'a, B: Backing, T: ?Sized> PartialEq<&'a T> for GenericNomBytes<B>
+where
+    GenericNomBytes<B>: PartialEq<T>,
+{
+    #[inline]
+    fn eq(&self, other: &&'a T) -> bool {
+        *self == **other
+    }
+}
+
+impl<'a, B: Backing, T: ?Sized> PartialOrd<&'a T> for GenericNomBytes<B>
+where
+    GenericNomBytes<B>: PartialOrd<T>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &&'a T) -> Option<core::cmp::Ordering> {
+        self.partial_cmp(&**other)
+    }
+}
+
 // Borrowed and adapted from
 //  <https://github.com/w4/nom-bytes/blob/2ede4dc22f1c303a2377c556d1a3b3f42464a0e7/src/lib.rs#L88>
-#[cfg(test)]
+#[cfg(all(test, feature = "nom7"))]
 mod tests {
     use crate::NomBytes;
+    use alloc::string::String;
+    use alloc::vec::Vec;
     use bytes::Bytes;
     use nom::bytes::complete::take_till;
-    use nom::{AsBytes, Offset, Slice};
+    use nom::{Offset, Slice};
 
     #[test]
     fn it_works() {
@@ -528,4 +988,139 @@ mod tests {
         assert_eq!(empty.to_bytes(), Bytes::from_static(b""));
         assert_eq!(empty.as_bytes(), b"");
     }
+
+    #[test]
+    fn slices_remember_their_origin() {
+        let input = NomBytes::from("key = value; rest");
+        let (_, value) =
+            take_till::<_, _, nom::error::Error<NomBytes>>(|v| v == b';')(input.slice(6..))
+                .unwrap();
+        drop(input);
+
+        assert_eq!(value.as_bytes(), b"value");
+        assert_eq!(value.span(), 6..11);
+        assert_eq!(value.offset_in_origin(), 6);
+        assert_eq!(value.origin().as_bytes(), b"key = value; rest");
+        assert_eq!(value.origin().span(), 0..17);
+
+        assert_eq!(value.clone().into_raw().1, 6..11);
+        assert_eq!(value.slice(1..3).span(), 7..9);
+        assert_eq!(value.slice(5..).span(), 11..11);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn slicing_past_the_end_panics() {
+        let nb = NomBytes::from("hello").slice(1..4);
+        let _ = nb.slice(2..4);
+    }
+
+    #[test]
+    fn checks_offsets_across_origins() {
+        let input = NomBytes::from("hello, world");
+        let hello = input.slice(..5);
+        let world = input.slice(7..);
+
+        assert_eq!(input.try_offset(&world), Some(7));
+        assert_eq!(input.try_offset(&input.slice(12..)), Some(12));
+        assert_eq!(hello.try_offset(&world), None);
+        assert_eq!(world.try_offset(&input), None);
+        assert_eq!(input.try_offset(&NomBytes::from("world")), None);
+
+        assert!(hello.same_origin(&world));
+        assert!(!hello.ptr_eq(&world));
+        assert!(world.ptr_eq(&input.slice(7..)));
+
+        // A round trip through `Bytes` keeps the memory but not the origin
+        let copy = NomBytes::new(world.to_bytes());
+        assert!(copy.ptr_eq(&world));
+        assert!(!copy.same_origin(&world));
+        assert_eq!(input.try_offset(&copy), Some(7));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "isn't a slice of this one")]
+    fn offset_across_origins_panics_in_debug() {
+        let input = NomBytes::from("hello");
+        let _ = input.offset(&NomBytes::from("hello"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_only_the_slice() {
+        let nb = NomBytes::from("hello, world").slice(7..);
+        let json = serde_json::to_string(&nb).unwrap();
+        let nb: NomBytes = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(nb.as_bytes(), b"world");
+        assert_eq!(nb.span(), 0..5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes_ranges() {
+        use crate::{RangeType, RawNomBytes};
+
+        let raw =
+            |range| serde_json::to_string(&RawNomBytes(Bytes::from("hello"), Some(range))).unwrap();
+
+        let nb: NomBytes = serde_json::from_str(&raw(RangeType::RangeFrom(1..))).unwrap();
+        assert_eq!(nb.as_bytes(), b"ello");
+        assert_eq!(nb.span(), 1..5);
+
+        assert!(serde_json::from_str::<NomBytes>(&raw(RangeType::Range(3..9))).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn looks_up_keys_by_bytes() {
+        use std::collections::HashMap;
+
+        let input = NomBytes::from("name=ferris");
+        let mut fields = HashMap::new();
+        fields.insert(input.slice(..4), input.slice(5..));
+
+        assert_eq!(fields.get(&b"name"[..]).unwrap(), "ferris");
+        assert_eq!(
+            fields.get(&NomBytes::from("name")).map(|v| v.len()),
+            Some(6)
+        );
+        assert!(!fields.contains_key(&b"ferris"[..]));
+    }
+
+    #[test]
+    fn compares_with_other_byte_containers() {
+        use core::cmp::Ordering::{Greater, Less};
+
+        let nb = NomBytes::from("abc");
+        let (slice, string) = (&b"abc"[..], String::from("abc"));
+        let (bytes, vec) = (Bytes::from("abc"), b"abc".to_vec());
+
+        assert_eq!(nb, *slice);
+        assert_eq!(*slice, nb);
+        assert_eq!(nb, slice);
+        assert_eq!(slice, nb);
+        assert_eq!(nb, "abc");
+        assert_eq!("abc", nb);
+        assert_eq!(nb, string);
+        assert_eq!(string, nb);
+        assert_eq!(nb, bytes);
+        assert_eq!(bytes, nb);
+        assert_eq!(nb, vec);
+        assert_eq!(vec, nb);
+        assert_ne!(nb, "abd");
+        assert_ne!("abd", nb);
+
+        assert_eq!(nb.partial_cmp("abd"), Some(Less));
+        assert_eq!("abd".partial_cmp(&nb), Some(Greater));
+        assert_eq!(nb.partial_cmp(&b"ab".to_vec()), Some(Greater));
+        assert_eq!(Bytes::from("b").partial_cmp(&nb), Some(Greater));
+        assert!(slice <= nb);
+        assert!(nb >= "ab");
+
+        // And through `Deref`, everything a `[u8]` can do
+        assert!(nb.starts_with(b"ab"));
+        assert_eq!(nb.iter().rev().copied().collect::<Vec<_>>(), b"cba");
+    }
 }
@@ -0,0 +1,324 @@
+use crate::NomBytes;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// An index of where the lines in a [`NomBytes`] start, for looking up the
+/// line and column of an offset, or the byte range of a line, in
+/// logarithmic time.
+///
+/// Columns are available counted in bytes, in `char`s and in UTF-16 code
+/// units, which is what editors and the language server protocol use. To
+/// make the last two fast, the index also remembers where the multi-byte
+/// characters are, so building it takes a single pass over the source.
+///
+/// Lines end at `\n`, so a `\r\n` line ending counts the `\r` as part of
+/// the line. Bytes that aren't valid UTF-8 count as one `char` and one
+/// UTF-16 code unit each, except for stray continuation bytes, which count
+/// as part of the character before them.
+///
+/// # Examples
+///
+/// ```
+/// use nombytes::{LineCol, LineIndex, NomBytes};
+///
+/// let source = NomBytes::from("fn main() {\n    let π = 3.14;\n}\n");
+/// let index = LineIndex::new(&source);
+///
+/// // The `=` after the π
+/// assert_eq!(
+///     index.line_col(23),
+///     Some(LineCol {
+///         line: 2,
+///         column: 12,
+///         char_column: 11,
+///         utf16_column: 11,
+///     })
+/// );
+/// assert_eq!(index.line_range(2), Some(12..31));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    source: NomBytes,
+    line_starts: Vec<usize>,
+    // Runs of UTF-8 continuation bytes, as `(start, len)`, which is what
+    // makes a `char` or UTF-16 column differ from a byte column.
+    runs: Vec<(usize, usize)>,
+    // How many continuation bytes, and how many characters that take two
+    // UTF-16 code units, come before each run, plus the totals at the end.
+    continuation_before: Vec<usize>,
+    surrogates_before: Vec<usize>,
+}
+
+/// A line and column in a [`LineIndex`], all of them 1-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LineCol {
+    /// The line number.
+    pub line: usize,
+    /// The column, counted in bytes.
+    pub column: usize,
+    /// The column, counted in `char`s.
+    pub char_column: usize,
+    /// The column, counted in UTF-16 code units.
+    pub utf16_column: usize,
+}
+
+impl LineIndex {
+    /// Builds the index for `source`.
+    #[must_use]
+    pub fn new(source: &NomBytes) -> Self {
+        let mut line_starts = vec![0];
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for (i, b) in source.as_bytes().iter().enumerate() {
+            match b {
+                b'\n' => line_starts.push(i + 1),
+                0x80..=0xBF => match runs.last_mut() {
+                    Some((start, len)) if *start + *len == i => *len += 1,
+                    _ => runs.push((i, 1)),
+                },
+                _ => {}
+            }
+        }
+
+        let mut continuation_before = Vec::with_capacity(runs.len() + 1);
+        let mut surrogates_before = Vec::with_capacity(runs.len() + 1);
+        let (mut continuation, mut surrogates) = (0, 0);
+        for (_, len) in &runs {
+            continuation_before.push(continuation);
+            surrogates_before.push(surrogates);
+            continuation += len;
+            // Only characters outside the BMP take four bytes
+            if *len >= 3 {
+                surrogates += 1;
+            }
+        }
+        continuation_before.push(continuation);
+        surrogates_before.push(surrogates);
+
+        Self {
+            source: source.clone(),
+            line_starts,
+            runs,
+            continuation_before,
+            surrogates_before,
+        }
+    }
+
+    /// Returns the source this index was built for.
+    #[inline]
+    pub fn source(&self) -> &NomBytes {
+        &self.source
+    }
+
+    /// Returns the number of lines. A source that ends with a `\n` has an
+    /// empty last line after it.
+    #[inline]
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the byte range of the given 1-based line, including its line
+    /// ending, or `None` if there's no such line.
+    #[must_use]
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .copied()
+            .unwrap_or_else(|| self.source.as_bytes().len());
+        Some(start..end)
+    }
+
+    /// Returns the line and column of the given byte offset, or `None` if
+    /// it's past the end of the source.
+    ///
+    /// The end of the source is a valid offset, and an offset in the middle
+    /// of a character has the same `char` and UTF-16 columns as the start of
+    /// the character.
+    #[must_use]
+    pub fn line_col(&self, offset: usize) -> Option<LineCol> {
+        if offset > self.source.as_bytes().len() {
+            return None;
+        }
+        Some(self.line_col_in_bounds(offset))
+    }
+
+    /// Returns the line and column `slice` starts at, or `None` if it isn't
+    /// a slice of the source this index was built for.
+    #[must_use]
+    pub fn locate(&self, slice: &NomBytes) -> Option<LineCol> {
+        self.line_col(self.source.try_offset(slice)?)
+    }
+
+    /// [`line_col`](Self::line_col) for an offset that's known not to be
+    /// past the end of the source.
+    fn line_col_in_bounds(&self, offset: usize) -> LineCol {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = offset - line_start;
+        let mut chars = column - (self.continuation_to(offset) - self.continuation_to(line_start));
+        if self.splits_char(offset) {
+            chars -= 1;
+        }
+        let surrogates = self.surrogates_to(offset) - self.surrogates_to(line_start);
+
+        LineCol {
+            line,
+            column: column + 1,
+            char_column: chars + 1,
+            utf16_column: chars + surrogates + 1,
+        }
+    }
+
+    /// The number of continuation bytes before `offset`.
+    fn continuation_to(&self, offset: usize) -> usize {
+        let run = self.runs.partition_point(|(start, _)| *start < offset);
+        match run.checked_sub(1) {
+            Some(last) => {
+                let (start, len) = self.runs[last];
+                self.continuation_before[last] + len.min(offset - start)
+            }
+            None => 0,
+        }
+    }
+
+    /// Whether `offset` is between the lead byte of a character and its
+    /// continuation bytes.
+    fn splits_char(&self, offset: usize) -> bool {
+        let run = self.runs.partition_point(|(start, _)| *start <= offset);
+        match run.checked_sub(1).map(|last| self.runs[last]) {
+            Some((start, len)) if offset < start + len => {
+                start > 0 && self.source.as_bytes()[start - 1] >= 0xC0
+            }
+            _ => false,
+        }
+    }
+
+    /// The number of characters that take two UTF-16 code units that end at
+    /// or before `offset`.
+    fn surrogates_to(&self, offset: usize) -> usize {
+        let run = self
+            .runs
+            .partition_point(|(start, len)| *start + *len <= offset);
+        self.surrogates_before[run]
+    }
+}
+
+impl NomBytes {
+    /// Returns the line and column this `NomBytes` starts at in its
+    /// [`origin`](Self::origin).
+    ///
+    /// This builds a [`LineIndex`] for the whole origin every time it's
+    /// called, which takes a pass over all of it. If you need to locate more
+    /// than a handful of slices of the same origin, build the index once and
+    /// use [`LineIndex::locate`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomBytes;
+    ///
+    /// // "ond", sliced out of "first\nsecond"
+    /// let word = NomBytes::from_raw(("first\nsecond".into(), 9..12));
+    ///
+    /// let location = word.line_col();
+    /// assert_eq!((location.line, location.column), (2, 4));
+    /// ```
+    #[must_use]
+    pub fn line_col(&self) -> LineCol {
+        LineIndex::new(&self.origin()).line_col_in_bounds(self.offset_in_origin())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineCol, LineIndex};
+    use crate::NomBytes;
+
+    fn columns(index: &LineIndex, offset: usize) -> (usize, usize, usize, usize) {
+        let lc = index.line_col(offset).unwrap();
+        (lc.line, lc.column, lc.char_column, lc.utf16_column)
+    }
+
+    #[test]
+    fn finds_lines() {
+        let index = LineIndex::new(&NomBytes::from("ab\r\n\ncd\n"));
+
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_range(0), None);
+        assert_eq!(index.line_range(1), Some(0..4));
+        assert_eq!(index.line_range(2), Some(4..5));
+        assert_eq!(index.line_range(3), Some(5..8));
+        assert_eq!(index.line_range(4), Some(8..8));
+        assert_eq!(index.line_range(5), None);
+
+        assert_eq!(columns(&index, 0), (1, 1, 1, 1));
+        assert_eq!(columns(&index, 3), (1, 4, 4, 4));
+        assert_eq!(columns(&index, 4), (2, 1, 1, 1));
+        assert_eq!(columns(&index, 7), (3, 3, 3, 3));
+        assert_eq!(columns(&index, 8), (4, 1, 1, 1));
+        assert_eq!(index.line_col(9), None);
+    }
+
+    #[test]
+    fn counts_chars_and_utf16_units() {
+        // 'é' is 2 bytes and 1 UTF-16 unit, '€' is 3 and 1, '😀' is 4 and 2
+        let index = LineIndex::new(&NomBytes::from("aé€😀b\n😀😀x"));
+
+        assert_eq!(columns(&index, 1), (1, 2, 2, 2));
+        assert_eq!(columns(&index, 3), (1, 4, 3, 3));
+        assert_eq!(columns(&index, 6), (1, 7, 4, 4));
+        assert_eq!(columns(&index, 10), (1, 11, 5, 6));
+        assert_eq!(columns(&index, 11), (1, 12, 6, 7));
+        assert_eq!(columns(&index, 12), (2, 1, 1, 1));
+        assert_eq!(columns(&index, 16), (2, 5, 2, 3));
+        assert_eq!(columns(&index, 20), (2, 9, 3, 5));
+
+        // In the middle of the first 😀 on the second line
+        assert_eq!(columns(&index, 14), (2, 3, 1, 1));
+    }
+
+    #[test]
+    fn handles_invalid_utf8() {
+        let index = LineIndex::new(&NomBytes::from(&b"\xff\x80\x80a\n\xe9x"[..]));
+
+        assert_eq!(columns(&index, 3), (1, 4, 2, 2));
+        assert_eq!(columns(&index, 6), (2, 2, 2, 2));
+    }
+
+    #[test]
+    fn locates_slices() {
+        let source = NomBytes::from("key = value\nnext = 1\n");
+        let index = LineIndex::new(&source);
+
+        assert_eq!(
+            index.locate(&source.slice_range(19..)),
+            Some(LineCol {
+                line: 2,
+                column: 8,
+                char_column: 8,
+                utf16_column: 8
+            })
+        );
+        assert_eq!(
+            source.slice_range(21..).line_col(),
+            LineCol {
+                line: 3,
+                column: 1,
+                char_column: 1,
+                utf16_column: 1
+            }
+        );
+        assert_eq!(
+            source
+                .slice_range(12..14)
+                .slice_range(1..)
+                .line_col()
+                .column,
+            2
+        );
+        assert_eq!(index.locate(&NomBytes::from("value")), None);
+    }
+}
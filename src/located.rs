@@ -0,0 +1,595 @@
+use crate::NomBytes;
+use bytes::Bytes;
+use core::fmt::Display;
+use core::ops::RangeBounds;
+
+/// A [`NomBytes`] that keeps track of where in the original input it is
+/// located, much like `nom_locate`'s `LocatedSpan`, but owned and without
+/// any lifetimes.
+///
+/// Every slice made of a `LocatedNomBytes` knows its absolute byte offset,
+/// its 1-based line number and its 1-based column, so these can be stored
+/// next to the parsed values, e.g. in an AST.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "nom7")]
+/// # {
+/// use nom::bytes::complete::{tag, take_until};
+/// use nom::IResult;
+/// use nombytes::LocatedNomBytes;
+///
+/// fn find_key(input: LocatedNomBytes) -> IResult<LocatedNomBytes, LocatedNomBytes> {
+///     let (input, _) = take_until("key")(input)?;
+///     tag("key")(input)
+/// }
+///
+/// let input = LocatedNomBytes::from("first line\nsecond key\n");
+/// let (_, key) = find_key(input).unwrap();
+///
+/// assert_eq!(key.location_offset(), 18);
+/// assert_eq!(key.location_line(), 2);
+/// assert_eq!(key.get_column(), 8);
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocatedNomBytes {
+    fragment: NomBytes,
+    offset: usize,
+    line: usize,
+    column: usize,
+    utf8_column: usize,
+}
+
+impl LocatedNomBytes {
+    /// Creates a new `LocatedNomBytes` positioned at the very start of the
+    /// provided [`NomBytes`], i.e. at offset 0, line 1, column 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::{LocatedNomBytes, NomBytes};
+    ///
+    /// let lnb = LocatedNomBytes::new(NomBytes::from("hello"));
+    /// assert_eq!(lnb.location_offset(), 0);
+    /// assert_eq!(lnb.location_line(), 1);
+    /// assert_eq!(lnb.get_column(), 1);
+    /// ```
+    #[inline]
+    pub fn new(fragment: NomBytes) -> Self {
+        Self {
+            fragment,
+            offset: 0,
+            line: 1,
+            column: 1,
+            utf8_column: 1,
+        }
+    }
+
+    /// Returns the [`NomBytes`] this value currently covers.
+    #[inline]
+    pub fn fragment(&self) -> &NomBytes {
+        &self.fragment
+    }
+
+    /// Returns the [`NomBytes`] this value currently covers, discarding the
+    /// location information.
+    #[inline]
+    pub fn into_fragment(self) -> NomBytes {
+        self.fragment
+    }
+
+    /// Returns a byte slice to the contents of the fragment.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.fragment.as_bytes()
+    }
+
+    /// Returns the byte offset of the fragment from the start of the input
+    /// this value was originally created from.
+    #[inline]
+    pub fn location_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the 1-based line number the fragment starts on.
+    #[inline]
+    pub fn location_line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based column the fragment starts on, counted in bytes.
+    #[inline]
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the 1-based column the fragment starts on, counted in UTF-8
+    /// chars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "nom7")]
+    /// # {
+    /// use nom::Slice;
+    /// use nombytes::LocatedNomBytes;
+    ///
+    /// let lnb = LocatedNomBytes::from("ÆØÅ!");
+    /// let bang = lnb.slice(6..);
+    /// assert_eq!(bang.get_column(), 7);
+    /// assert_eq!(bang.get_utf8_column(), 4);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn get_utf8_column(&self) -> usize {
+        self.utf8_column
+    }
+
+    /// Slices the fragment and updates the location information to match.
+    ///
+    /// The slicing itself is done by [`NomBytes`], so empty slices keep their
+    /// position in the original input, and the number of consumed bytes is
+    /// found the same way nom's `Offset` finds it.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn slice_range<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let next = self.fragment.slice_range(range);
+        let consumed_len = self.fragment.offset_to(&next);
+        let consumed = &self.fragment.as_bytes()[..consumed_len];
+
+        // Not worth pulling in `bytecount` for this
+        #[allow(clippy::naive_bytecount)]
+        let newlines = consumed.iter().filter(|&&b| b == b'\n').count();
+        let (column, utf8_column) = match consumed.iter().rposition(|&b| b == b'\n') {
+            Some(last_newline) => {
+                let last_line = &consumed[last_newline + 1..];
+                (last_line.len() + 1, count_chars(last_line) + 1)
+            }
+            None => (
+                self.column + consumed_len,
+                self.utf8_column + count_chars(consumed),
+            ),
+        };
+
+        Self {
+            fragment: next,
+            offset: self.offset + consumed_len,
+            line: self.line + newlines,
+            column,
+            utf8_column,
+        }
+    }
+}
+
+/// Counts the UTF-8 chars in `bytes` by counting the bytes that aren't
+/// continuation bytes, so a char split across two slices is only counted once.
+#[inline]
+fn count_chars(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| (b & 0xC0) != 0x80).count()
+}
+
+impl Display for LocatedNomBytes {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fragment.fmt(f)
+    }
+}
+
+impl From<NomBytes> for LocatedNomBytes {
+    #[inline]
+    fn from(fragment: NomBytes) -> Self {
+        Self::new(fragment)
+    }
+}
+
+impl From<Bytes> for LocatedNomBytes {
+    #[inline]
+    fn from(bytes: Bytes) -> Self {
+        Self::new(NomBytes::new(bytes))
+    }
+}
+
+impl From<&'_ str> for LocatedNomBytes {
+    #[inline]
+    fn from(string: &'_ str) -> Self {
+        Self::new(NomBytes::from(string))
+    }
+}
+
+impl From<&'_ [u8]> for LocatedNomBytes {
+    #[inline]
+    fn from(byte_slice: &'_ [u8]) -> Self {
+        Self::new(NomBytes::from(byte_slice))
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl From<String> for LocatedNomBytes {
+    #[inline]
+    fn from(string: String) -> Self {
+        Self::new(NomBytes::from(string))
+    }
+}
+
+#[cfg(feature = "nom7")]
+mod nom7 {
+    use super::LocatedNomBytes;
+    use crate::NomBytes;
+    use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
+    use nom::{
+        AsBytes, Compare, FindSubstring, InputIter, InputLength, InputTake, InputTakeAtPosition,
+        Needed, Offset, Slice,
+    };
+
+    impl AsBytes for LocatedNomBytes {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            LocatedNomBytes::as_bytes(self)
+        }
+    }
+
+    impl InputIter for LocatedNomBytes {
+        type Item = <NomBytes as InputIter>::Item;
+        type Iter = <NomBytes as InputIter>::Iter;
+        type IterElem = <NomBytes as InputIter>::IterElem;
+
+        #[inline]
+        fn iter_indices(&self) -> Self::Iter {
+            self.fragment.iter_indices()
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::IterElem {
+            self.fragment.iter_elements()
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            self.fragment.position(predicate)
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            self.fragment.slice_index(count)
+        }
+    }
+
+    impl InputTake for LocatedNomBytes {
+        #[inline]
+        fn take(&self, count: usize) -> Self {
+            self.slice(..count)
+        }
+
+        #[inline]
+        fn take_split(&self, count: usize) -> (Self, Self) {
+            let prefix = self.slice(..count);
+            let suffix = self.slice(count..);
+            (suffix, prefix)
+        }
+    }
+
+    impl InputTakeAtPosition for LocatedNomBytes {
+        type Item = <Self as InputIter>::Item;
+
+        fn split_at_position<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.as_bytes().iter().position(|c| predicate(*c)) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position1<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: nom::error::ErrorKind,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.as_bytes().iter().position(|c| predicate(*c)) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position_complete<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.as_bytes().iter().position(|c| predicate(*c)) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Ok(self.take_split(self.input_len())),
+            }
+        }
+
+        fn split_at_position1_complete<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: nom::error::ErrorKind,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            let bytes = self.as_bytes();
+            match bytes.iter().position(|c| predicate(*c)) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => {
+                    if bytes.is_empty() {
+                        Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+                    } else {
+                        Ok(self.take_split(self.input_len()))
+                    }
+                }
+            }
+        }
+    }
+
+    impl InputLength for LocatedNomBytes {
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.as_bytes().len()
+        }
+    }
+
+    macro_rules! located_nom_bytes_slice {
+        ($range_ty:ty) => {
+            impl Slice<$range_ty> for LocatedNomBytes {
+                #[inline]
+                fn slice(&self, range: $range_ty) -> Self {
+                    self.slice_range(range)
+                }
+            }
+        };
+    }
+
+    located_nom_bytes_slice!(Range<usize>);
+    located_nom_bytes_slice!(RangeTo<usize>);
+    located_nom_bytes_slice!(RangeFrom<usize>);
+    located_nom_bytes_slice!(RangeFull);
+
+    impl Offset for LocatedNomBytes {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            second.offset - self.offset
+        }
+    }
+
+    impl<T> Compare<T> for LocatedNomBytes
+    where
+        NomBytes: Compare<T>,
+    {
+        #[inline]
+        fn compare(&self, t: T) -> nom::CompareResult {
+            self.fragment.compare(t)
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: T) -> nom::CompareResult {
+            self.fragment.compare_no_case(t)
+        }
+    }
+
+    impl<T> FindSubstring<T> for LocatedNomBytes
+    where
+        NomBytes: FindSubstring<T>,
+    {
+        fn find_substring(&self, substr: T) -> Option<usize> {
+            self.fragment.find_substring(substr)
+        }
+    }
+}
+
+#[cfg(feature = "nom8")]
+mod nom8 {
+    use super::LocatedNomBytes;
+    use crate::NomBytes;
+    use nom8::{AsBytes, Compare, CompareResult, FindSubstring, Input, Needed, Offset};
+
+    impl AsBytes for LocatedNomBytes {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            LocatedNomBytes::as_bytes(self)
+        }
+    }
+
+    impl Input for LocatedNomBytes {
+        type Item = <NomBytes as Input>::Item;
+        type Iter = <NomBytes as Input>::Iter;
+        type IterIndices = <NomBytes as Input>::IterIndices;
+
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.as_bytes().len()
+        }
+
+        #[inline]
+        fn take(&self, index: usize) -> Self {
+            self.slice_range(..index)
+        }
+
+        #[inline]
+        fn take_from(&self, index: usize) -> Self {
+            self.slice_range(index..)
+        }
+
+        #[inline]
+        fn take_split(&self, index: usize) -> (Self, Self) {
+            let prefix = self.slice_range(..index);
+            let suffix = self.slice_range(index..);
+            (suffix, prefix)
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            self.fragment.position(predicate)
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::Iter {
+            self.fragment.iter_elements()
+        }
+
+        #[inline]
+        fn iter_indices(&self) -> Self::IterIndices {
+            self.fragment.iter_indices()
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            self.fragment.slice_index(count)
+        }
+    }
+
+    impl Offset for LocatedNomBytes {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            second.offset - self.offset
+        }
+    }
+
+    impl<T> Compare<T> for LocatedNomBytes
+    where
+        NomBytes: Compare<T>,
+    {
+        #[inline]
+        fn compare(&self, t: T) -> CompareResult {
+            self.fragment.compare(t)
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: T) -> CompareResult {
+            self.fragment.compare_no_case(t)
+        }
+    }
+
+    impl<T> FindSubstring<T> for LocatedNomBytes
+    where
+        NomBytes: FindSubstring<T>,
+    {
+        fn find_substring(&self, substr: T) -> Option<usize> {
+            self.fragment.find_substring(substr)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::LocatedNomBytes;
+        use nom8::bytes::complete::{tag, take_until};
+        use nom8::Parser;
+
+        #[test]
+        fn tracks_location() {
+            let input = LocatedNomBytes::from("one\ntwo\nthree");
+
+            let (_, three) = (
+                take_until::<_, _, nom8::error::Error<LocatedNomBytes>>("three"),
+                tag("three"),
+            )
+                .parse(input)
+                .map(|(rest, (_, three))| (rest, three))
+                .unwrap();
+
+            assert_eq!(three.location_offset(), 8);
+            assert_eq!(three.location_line(), 3);
+            assert_eq!(three.get_column(), 1);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "nom7"))]
+mod tests {
+    use crate::LocatedNomBytes;
+    use nom::bytes::complete::{tag, take_until};
+    use nom::character::complete::{alpha1, line_ending, multispace0};
+    use nom::multi::fold_many1;
+    use nom::sequence::{terminated, tuple};
+    use nom::{IResult, InputTake, Offset, Slice};
+
+    #[test]
+    fn tracks_lines_and_columns() {
+        type Position = (usize, usize, usize);
+
+        fn positions(input: LocatedNomBytes) -> IResult<LocatedNomBytes, ([Position; 4], usize)> {
+            fold_many1(
+                terminated(alpha1, multispace0),
+                || ([(0, 0, 0); 4], 0),
+                |(mut acc, len), w: LocatedNomBytes| {
+                    acc[len] = (w.location_offset(), w.location_line(), w.get_column());
+                    (acc, len + 1)
+                },
+            )(input)
+        }
+
+        let (_, (positions, len)) =
+            positions(LocatedNomBytes::from("alpha beta\ngamma\n\n  delta")).unwrap();
+
+        assert_eq!(len, 4);
+        assert_eq!(positions, [(0, 1, 1), (6, 1, 7), (11, 2, 1), (20, 4, 3)]);
+    }
+
+    #[test]
+    fn tracks_utf8_columns() {
+        let input = LocatedNomBytes::from("¡olé! ¿qué?\nsí");
+        let (rest, _) =
+            take_until::<_, _, nom::error::Error<LocatedNomBytes>>("¿qué")(input).unwrap();
+        assert_eq!(rest.get_column(), 9);
+        assert_eq!(rest.get_utf8_column(), 7);
+
+        // Splitting a char in two doesn't count it twice.
+        let half = rest.slice(1..);
+        assert_eq!(half.get_utf8_column(), 8);
+        assert_eq!(half.slice(1..).get_utf8_column(), 8);
+
+        let (rest, _) = tuple((
+            take_until::<_, _, nom::error::Error<LocatedNomBytes>>("\n"),
+            line_ending,
+        ))(rest)
+        .unwrap();
+        assert_eq!((rest.location_line(), rest.get_utf8_column()), (2, 1));
+    }
+
+    #[test]
+    fn empty_slice_keeps_location() {
+        let input = LocatedNomBytes::from("abc\ndef");
+        let (end, all) = input.take_split(7);
+        assert_eq!(all.as_bytes(), b"abc\ndef");
+        assert_eq!(end.location_offset(), 7);
+        assert_eq!((end.location_line(), end.get_column()), (2, 4));
+        assert_eq!(input.offset(&end), 7);
+
+        // Even after re-slicing
+        let reslice = end.slice(0..);
+        assert_eq!(reslice.location_offset(), 7);
+        assert_eq!(input.fragment().offset_to(reslice.fragment()), 7);
+    }
+
+    #[test]
+    fn tag_on_located_input() {
+        let (rest, hello) = tag::<_, _, nom::error::Error<LocatedNomBytes>>("hello")(
+            LocatedNomBytes::from("hello\nworld"),
+        )
+        .unwrap();
+        assert_eq!(hello.as_bytes(), b"hello");
+        assert_eq!(rest.location_offset(), 5);
+    }
+}
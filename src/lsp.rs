@@ -0,0 +1,204 @@
+use crate::{LineIndex, NomBytes};
+use lsp_types::{Position, Range};
+
+// Positions in the language server protocol are 0-based lines and columns
+// counted in UTF-16 code units, where a column past the end of its line means
+// the end of the line, before its line ending.
+
+impl LineIndex {
+    /// Returns the LSP [`Position`] of the given byte offset, or `None` if
+    /// it's past the end of the source.
+    ///
+    /// An offset inside a `\r\n` line ending is placed at the end of its
+    /// line, and one in the middle of a character at the start of it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lsp_types::Position;
+    /// use nombytes::{LineIndex, NomBytes};
+    ///
+    /// let index = LineIndex::new(&NomBytes::from("let 😀 = 1;\r\nnext"));
+    /// assert_eq!(index.position(9), Some(Position::new(0, 7)));
+    /// assert_eq!(index.position(15), Some(Position::new(1, 0)));
+    /// ```
+    #[must_use]
+    pub fn position(&self, offset: usize) -> Option<Position> {
+        let lc = self.line_col(offset)?;
+        let end = self.content_end(lc.line)?;
+        let lc = if offset > end {
+            self.line_col(end)?
+        } else {
+            lc
+        };
+
+        Some(Position::new(
+            u32::try_from(lc.line - 1).ok()?,
+            u32::try_from(lc.utf16_column - 1).ok()?,
+        ))
+    }
+
+    /// Returns the byte offset of the given LSP [`Position`], or `None` if
+    /// its line is past the end of the source.
+    ///
+    /// A column past the end of its line is the end of the line, before its
+    /// line ending, and one in the middle of a surrogate pair is the start of
+    /// the character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lsp_types::Position;
+    /// use nombytes::{LineIndex, NomBytes};
+    ///
+    /// let index = LineIndex::new(&NomBytes::from("let 😀 = 1;\r\nnext"));
+    /// assert_eq!(index.offset(Position::new(0, 7)), Some(9));
+    /// assert_eq!(index.offset(Position::new(0, 100)), Some(13));
+    /// assert_eq!(index.offset(Position::new(2, 0)), None);
+    /// ```
+    #[must_use]
+    pub fn offset(&self, position: Position) -> Option<usize> {
+        let line = usize::try_from(position.line).ok()? + 1;
+        let start = self.line_range(line)?.start;
+        let end = self.content_end(line)?;
+        let target = usize::try_from(position.character).ok()?;
+
+        let mut units = 0;
+        for (i, b) in self.source().as_bytes()[start..end].iter().enumerate() {
+            // Continuation bytes are part of the character before them
+            if (0x80..=0xBF).contains(b) {
+                continue;
+            }
+            let width = if *b >= 0xF0 { 2 } else { 1 };
+            if units + width > target {
+                return Some(start + i);
+            }
+            units += width;
+        }
+        Some(end)
+    }
+
+    /// Returns the LSP [`Range`] covering the given byte range, or `None` if
+    /// it goes past the end of the source.
+    #[must_use]
+    pub fn lsp_range(&self, range: core::ops::Range<usize>) -> Option<Range> {
+        Some(Range::new(
+            self.position(range.start)?,
+            self.position(range.end)?,
+        ))
+    }
+
+    /// Returns the byte range covered by the given LSP [`Range`], or `None`
+    /// if either end is past the end of the source.
+    #[must_use]
+    pub fn byte_range(&self, range: Range) -> Option<core::ops::Range<usize>> {
+        let start = self.offset(range.start)?;
+        let end = self.offset(range.end)?;
+        Some(start..end.max(start))
+    }
+
+    /// Returns the LSP [`Range`] `slice` covers, or `None` if it isn't a
+    /// slice of the source this index was built for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lsp_types::{Position, Range};
+    /// use nombytes::{LineIndex, NomBytes};
+    ///
+    /// let source = NomBytes::from("a = 1\r\nb = 2\r\n");
+    /// let index = LineIndex::new(&source);
+    ///
+    /// let b = NomBytes::new(source.to_bytes().slice(7..8));
+    /// let range = index.lsp_range_of(&b).unwrap();
+    /// assert_eq!(range, Range::new(Position::new(1, 0), Position::new(1, 1)));
+    /// assert_eq!(index.slice(range).unwrap().as_bytes(), b"b");
+    /// ```
+    #[must_use]
+    pub fn lsp_range_of(&self, slice: &NomBytes) -> Option<Range> {
+        let start = self.source().try_offset(slice)?;
+        self.lsp_range(start..start + slice.as_bytes().len())
+    }
+
+    /// Returns the slice of the source the given LSP [`Range`] covers, or
+    /// `None` if either end is past the end of the source.
+    #[must_use]
+    pub fn slice(&self, range: Range) -> Option<NomBytes> {
+        Some(self.source().slice_range(self.byte_range(range)?))
+    }
+
+    /// The offset the given 1-based line's contents end at, i.e. the start
+    /// of its line ending, if it has one.
+    fn content_end(&self, line: usize) -> Option<usize> {
+        let range = self.line_range(line)?;
+        let line = &self.source().as_bytes()[range.clone()];
+        let len = match line {
+            [.., b'\r', b'\n'] => line.len() - 2,
+            [.., b'\n'] => line.len() - 1,
+            _ => line.len(),
+        };
+        Some(range.start + len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LineIndex, NomBytes};
+    use lsp_types::{Position, Range};
+
+    #[test]
+    fn converts_surrogate_pairs() {
+        // '😀' is 4 bytes and 2 UTF-16 units, 'é' is 2 bytes and 1 unit
+        let index = LineIndex::new(&NomBytes::from("a😀é😀b"));
+
+        for (offset, character) in [(0, 0), (1, 1), (5, 3), (7, 4), (11, 6), (12, 7)] {
+            assert_eq!(index.position(offset), Some(Position::new(0, character)));
+            assert_eq!(index.offset(Position::new(0, character)), Some(offset));
+        }
+
+        // In the middle of the first 😀, either way
+        assert_eq!(index.position(3), Some(Position::new(0, 1)));
+        assert_eq!(index.offset(Position::new(0, 2)), Some(1));
+
+        assert_eq!(index.position(13), None);
+    }
+
+    #[test]
+    fn handles_crlf_line_endings() {
+        let index = LineIndex::new(&NomBytes::from("ab\r\ncd\r\n\r\nef\n"));
+
+        assert_eq!(index.position(2), Some(Position::new(0, 2)));
+        // Both bytes of the line ending are at the end of the line
+        assert_eq!(index.position(3), Some(Position::new(0, 2)));
+        assert_eq!(index.position(4), Some(Position::new(1, 0)));
+        assert_eq!(index.position(8), Some(Position::new(2, 0)));
+        assert_eq!(index.position(12), Some(Position::new(3, 2)));
+        assert_eq!(index.position(13), Some(Position::new(4, 0)));
+
+        assert_eq!(index.offset(Position::new(0, 2)), Some(2));
+        assert_eq!(index.offset(Position::new(0, 3)), Some(2));
+        assert_eq!(index.offset(Position::new(1, 9)), Some(6));
+        assert_eq!(index.offset(Position::new(2, 0)), Some(8));
+        assert_eq!(index.offset(Position::new(3, 1)), Some(11));
+        assert_eq!(index.offset(Position::new(4, 0)), Some(13));
+        assert_eq!(index.offset(Position::new(5, 0)), None);
+    }
+
+    #[test]
+    fn converts_ranges_and_slices() {
+        let source = NomBytes::from("key = \"😀\"\r\nnext = 1");
+        let index = LineIndex::new(&source);
+        let value = source.slice_range(6..12);
+
+        let range = Range::new(Position::new(0, 6), Position::new(0, 10));
+        assert_eq!(index.lsp_range_of(&value), Some(range));
+        assert_eq!(index.byte_range(range), Some(6..12));
+        assert_eq!(index.slice(range).unwrap().as_bytes(), "\"😀\"".as_bytes());
+
+        assert_eq!(index.lsp_range_of(&NomBytes::from("next")), None);
+
+        // A backwards range is empty rather than inverted
+        let backwards = Range::new(Position::new(1, 4), Position::new(0, 0));
+        assert_eq!(index.byte_range(backwards), Some(18..18));
+    }
+}
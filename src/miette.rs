@@ -1,6 +1,6 @@
-use crate::NomBytes;
-use miette::SourceCode;
-use nom::AsBytes;
+use crate::{NomBytes, SourceMap};
+use miette::{MietteSpanContents, SourceCode};
+use std::fmt::Display;
 
 impl SourceCode for NomBytes {
     fn read_span<'a>(
@@ -14,9 +14,164 @@ impl SourceCode for NomBytes {
     }
 }
 
+// Spans are global offsets, which are turned into offsets in the source they
+// fall in to read the contents, and back again so the labels line up.
+impl<Name: Display + Send + Sync> SourceCode for SourceMap<Name> {
+    fn read_span<'a>(
+        &'a self,
+        span: &miette::SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<std::boxed::Box<dyn miette::SpanContents<'a> + 'a>, miette::MietteError> {
+        let (id, offset) = self
+            .lookup_offset(span.offset())
+            .ok_or(miette::MietteError::OutOfBounds)?;
+        let file = self.file(id).ok_or(miette::MietteError::OutOfBounds)?;
+
+        let local = miette::SourceSpan::new(offset.into(), span.len().into());
+        let contents =
+            file.source()
+                .read_span(&local, context_lines_before, context_lines_after)?;
+        let start = file.span().start + contents.span().offset();
+
+        Ok(Box::new(MietteSpanContents::new_named(
+            file.name().to_string(),
+            contents.data(),
+            miette::SourceSpan::new(start.into(), contents.span().len().into()),
+            contents.line(),
+            contents.column(),
+            contents.line_count(),
+        )))
+    }
+}
+
+#[cfg(feature = "nom7")]
+pub use self::nom7::{into_report, NomDiagnostic};
+
+#[cfg(feature = "nom7")]
+mod nom7 {
+    use crate::labels::{self, Labels, FAILED, INCOMPLETE};
+    use crate::NomBytes;
+    use core::ops::Range;
+    use miette::{Diagnostic, LabeledSpan, NamedSource, SourceCode};
+    use std::fmt::Display;
+
+    /// A [`Diagnostic`] describing a `nom` error in a [`NomBytes`] input.
+    ///
+    /// The location of the error, and of any context it was given, are
+    /// turned into labels on the source code, so turning it into a
+    /// [`miette::Report`] gets you a report pointing at the spot where
+    /// parsing failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nom::bytes::complete::tag;
+    /// use nom::error::{context, VerboseError};
+    /// use nom::sequence::preceded;
+    /// use nom::IResult;
+    /// use nombytes::{NomBytes, NomDiagnostic};
+    ///
+    /// fn greeting(input: NomBytes) -> IResult<NomBytes, NomBytes, VerboseError<NomBytes>> {
+    ///     context("greeting", preceded(tag("hello "), tag("world")))(input)
+    /// }
+    ///
+    /// let source = NomBytes::from("hello there");
+    /// let err = greeting(source.clone()).unwrap_err();
+    /// let report = miette::Report::new(NomDiagnostic::new(err, &source).with_name("greeting.txt"));
+    ///
+    /// let output = format!("{report:?}");
+    /// assert!(output.contains("greeting.txt"));
+    /// ```
+    #[derive(Debug)]
+    pub struct NomDiagnostic {
+        source: NomBytes,
+        name: Option<NamedSource>,
+        message: &'static str,
+        labels: Vec<LabeledSpan>,
+    }
+
+    impl NomDiagnostic {
+        /// Creates a new `NomDiagnostic` describing `err`, which was returned
+        /// by a parser that was given `source`.
+        pub fn new<E: Labels>(err: nom::Err<E>, source: &NomBytes) -> Self {
+            let (message, labels) = match err {
+                nom::Err::Error(e) | nom::Err::Failure(e) => (FAILED, e.labels(source)),
+                nom::Err::Incomplete(needed) => {
+                    (INCOMPLETE, vec![labels::incomplete(needed, source)])
+                }
+            };
+
+            Self {
+                source: source.clone(),
+                name: None,
+                message,
+                labels: labels.into_iter().map(labeled_span).collect(),
+            }
+        }
+
+        /// Gives the source code a name, typically the name of the file it
+        /// was read from, to show in the report.
+        #[must_use]
+        pub fn with_name(mut self, name: impl AsRef<str>) -> Self {
+            self.name = Some(NamedSource::new(name, self.source.clone()));
+            self
+        }
+    }
+
+    impl Display for NomDiagnostic {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    impl std::error::Error for NomDiagnostic {}
+
+    impl Diagnostic for NomDiagnostic {
+        fn source_code(&self) -> Option<&dyn SourceCode> {
+            match &self.name {
+                Some(named) => Some(named),
+                None => Some(&self.source),
+            }
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+            Some(Box::new(self.labels.iter().cloned()))
+        }
+    }
+
+    /// Turns the `nom` error `err`, returned by a parser that was given
+    /// `source`, into a [`miette::Report`].
+    ///
+    /// This is a shorthand for wrapping a [`NomDiagnostic`] in a report.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nom::character::complete::digit1;
+    /// use nom::IResult;
+    /// use nombytes::{into_report, NomBytes};
+    ///
+    /// fn number(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+    ///     digit1(input)
+    /// }
+    ///
+    /// let source = NomBytes::from("abc");
+    /// let report = into_report(number(source.clone()).unwrap_err(), &source);
+    /// assert_eq!(report.to_string(), "failed to parse input");
+    /// ```
+    pub fn into_report<E: Labels>(err: nom::Err<E>, source: &NomBytes) -> miette::Report {
+        miette::Report::new(NomDiagnostic::new(err, source))
+    }
+
+    fn labeled_span((span, label): (Range<usize>, String)) -> LabeledSpan {
+        LabeledSpan::new(Some(label), span.start, span.end - span.start)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::NomBytes;
+    use crate::{NomBytes, SourceMap};
     use bytes::Bytes;
     use miette::{Diagnostic, SourceSpan};
     use std::error::Error;
@@ -52,4 +207,165 @@ mod tests {
         // worked like it should.
         assert!(output.contains("Hello"), "{output}");
     }
+
+    #[derive(Debug, Diagnostic)]
+    struct ErrorInSourceMap {
+        #[source_code]
+        src: SourceMap<&'static str>,
+
+        #[label = "here"]
+        err_span: SourceSpan,
+    }
+    impl Display for ErrorInSourceMap {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "Error")
+        }
+    }
+    impl Error for ErrorInSourceMap {}
+
+    #[test]
+    fn serves_spans_from_source_maps() {
+        let second = NomBytes::from("third line\nfourth line\n");
+        let mut src = SourceMap::new();
+        src.add("first.txt", NomBytes::from("first line\nsecond line\n"));
+        src.add("second.txt", second.clone());
+
+        let span = src.span(&second.slice_range(18..22)).unwrap();
+        let error = ErrorInSourceMap {
+            src,
+            err_span: (span.start, span.len()).into(),
+        };
+
+        let mut output = String::new();
+        miette::GraphicalReportHandler::new_themed(miette::GraphicalTheme::unicode_nocolor())
+            .render_report(&mut output, &error)
+            .unwrap();
+
+        assert!(output.contains("second.txt"), "{output}");
+        assert!(output.contains("fourth line"), "{output}");
+        assert!(!output.contains("second line"), "{output}");
+    }
+
+    #[cfg(feature = "nom7")]
+    mod diagnostics {
+        use crate::{into_report, NomBytes, NomDiagnostic};
+        use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme, LabeledSpan};
+        use nom::bytes::complete::tag;
+        use nom::bytes::streaming::tag as streaming_tag;
+        use nom::character::complete::{char, digit1};
+        use nom::combinator::map_res;
+        use nom::error::{context, VerboseError};
+        use nom::sequence::{delimited, preceded};
+        use nom::IResult;
+
+        fn render(diagnostic: &NomDiagnostic) -> String {
+            let mut output = String::new();
+            GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+                .render_report(&mut output, diagnostic)
+                .unwrap();
+            output
+        }
+
+        fn labels(diagnostic: &NomDiagnostic) -> Vec<LabeledSpan> {
+            diagnostic.labels().unwrap().collect()
+        }
+
+        #[test]
+        fn labels_simple_errors() {
+            fn number(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+                preceded(tag("n="), digit1)(input)
+            }
+
+            let source = NomBytes::from("n=abc");
+            let diagnostic = NomDiagnostic::new(number(source.clone()).unwrap_err(), &source);
+
+            assert_eq!(
+                labels(&diagnostic),
+                [LabeledSpan::new(Some("Digit".to_owned()), 2, 0)]
+            );
+        }
+
+        #[test]
+        fn skips_labels_outside_the_source() {
+            fn list(input: NomBytes) -> IResult<NomBytes, NomBytes, VerboseError<NomBytes>> {
+                context("list", delimited(char('['), digit1, char(']')))(input)
+            }
+
+            let source = NomBytes::from("[12;");
+            let other = NomBytes::from("[12;");
+            let diagnostic = NomDiagnostic::new(list(other).unwrap_err(), &source);
+
+            assert_eq!(labels(&diagnostic), []);
+            assert_eq!(diagnostic.to_string(), "failed to parse input");
+        }
+
+        #[test]
+        fn labels_verbose_error_contexts() {
+            fn list(input: NomBytes) -> IResult<NomBytes, NomBytes, VerboseError<NomBytes>> {
+                context("list", delimited(char('['), digit1, char(']')))(input)
+            }
+
+            let source = NomBytes::from("x = [12;");
+            let err = preceded(tag("x = "), list)(source.clone()).unwrap_err();
+            let diagnostic = NomDiagnostic::new(err, &source).with_name("list.txt");
+
+            assert_eq!(
+                labels(&diagnostic),
+                [
+                    LabeledSpan::new(Some("expected ']'".to_owned()), 7, 0),
+                    LabeledSpan::new(Some("while parsing list".to_owned()), 4, 3),
+                ]
+            );
+
+            let output = render(&diagnostic);
+            assert!(output.contains("failed to parse input"), "{output}");
+            assert!(output.contains("list.txt"), "{output}");
+            assert!(output.contains("while parsing list"), "{output}");
+        }
+
+        #[test]
+        fn labels_nombytes_errors() {
+            fn port(input: NomBytes) -> IResult<NomBytes, u16, crate::error::Error> {
+                context(
+                    "port",
+                    map_res(digit1, |digits: NomBytes| digits.to_str().parse::<u16>()),
+                )(input)
+            }
+
+            let source = NomBytes::from("port 99999");
+            let err = preceded(tag("port "), port)(source.clone()).unwrap_err();
+            let diagnostic = NomDiagnostic::new(err, &source);
+
+            assert_eq!(
+                labels(&diagnostic),
+                [
+                    LabeledSpan::new(
+                        Some("Map on Result: number too large to fit in target type".to_owned()),
+                        5,
+                        0
+                    ),
+                    LabeledSpan::new(Some("while parsing port".to_owned()), 5, 0),
+                ]
+            );
+        }
+
+        #[test]
+        fn reports_incomplete_input() {
+            let source = NomBytes::from("hel");
+            let err = streaming_tag::<_, _, nom::error::Error<NomBytes>>("hello")(source.clone())
+                .unwrap_err();
+            let report = into_report(err, &source);
+
+            assert_eq!(report.to_string(), "unexpected end of input");
+            let labels: Vec<_> = report.labels().unwrap().collect();
+            assert_eq!(
+                labels,
+                [LabeledSpan::new(
+                    Some("expected 2 more bytes".to_owned()),
+                    3,
+                    0
+                )]
+            );
+        }
+    }
 }
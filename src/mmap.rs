@@ -0,0 +1,121 @@
+use crate::NomBytes;
+use bytes::Bytes;
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+impl NomBytes {
+    /// Creates a new `NomBytes` that wraps a memory-mapped file.
+    ///
+    /// The mapping is moved into the underlying [`Bytes`], and every slice
+    /// made from the returned value shares it, so the file stays mapped for
+    /// as long as any of them are alive, and is unmapped once the last one is
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use memmap2::Mmap;
+    /// use nombytes::NomBytes;
+    ///
+    /// let file = std::fs::File::open("server.log")?;
+    /// // SAFETY: nothing else modifies server.log while we hold on to it
+    /// let log = NomBytes::from_mmap(unsafe { Mmap::map(&file)? });
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn from_mmap(mmap: Mmap) -> Self {
+        Self::new(Bytes::from_owner(mmap))
+    }
+
+    /// Memory-maps the file at `path` into a new `NomBytes`.
+    ///
+    /// This lets you parse files far larger than you'd want to read into
+    /// memory, and hand out parsed values that point straight into the file.
+    /// See [`from_mmap`](Self::from_mmap) for how long the mapping lives, and
+    /// [`read_file`](Self::read_file) for a fallback that reads the file into
+    /// memory instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nombytes::NomBytes;
+    ///
+    /// // SAFETY: nothing else modifies server.log while we hold on to it
+    /// let log = unsafe { NomBytes::open_file("server.log")? };
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file couldn't be opened or mapped.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other
+    /// process, for as long as the returned value or any slice of it is
+    /// alive. If it is, the contents of the `NomBytes` can change under its
+    /// feet, or reading them can crash the process. See [`Mmap::map`].
+    // The crate denies unsafe code, but there's no safe way to map a file:
+    // whether the mapping stays valid depends on what other processes do to
+    // the file, which nothing here can check. So this is an `unsafe fn` that
+    // passes that obligation on to the caller, and only calls `Mmap::map`
+    // under it.
+    #[allow(unsafe_code)]
+    pub unsafe fn open_file<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: upheld by the caller
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self::from_mmap(mmap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::NomBytes;
+    use std::io::Write;
+
+    fn temp_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("nombytes-{}-{name}", std::process::id()));
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(contents)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn slices_keep_the_mapping_alive() {
+        let path = temp_file("mapped", b"key=value\n");
+
+        // SAFETY: the file is only written before it's mapped
+        let nb = unsafe { NomBytes::open_file(&path).unwrap() };
+        let value = nb.to_bytes().slice(4..9);
+        drop(nb);
+
+        assert_eq!(value.as_ref(), b"value");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn maps_empty_files() {
+        let path = temp_file("empty", b"");
+
+        // SAFETY: the file is only written before it's mapped
+        let nb = unsafe { NomBytes::open_file(&path).unwrap() };
+        assert_eq!(nb.as_bytes(), b"");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reads_files() {
+        let path = temp_file("read", b"hello");
+
+        let nb = NomBytes::read_file(&path).unwrap();
+        assert_eq!(nb.as_bytes(), b"hello");
+        std::fs::remove_file(path).unwrap();
+    }
+}
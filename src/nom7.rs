@@ -0,0 +1,212 @@
+use crate::{Backing, GenericNomBytes, NomBytesIter};
+use core::iter::Enumerate;
+use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
+use nom::{
+    AsBytes, Compare, FindSubstring, InputIter, InputLength, InputTake, InputTakeAtPosition,
+    Needed, Offset, Slice,
+};
+
+impl<B: Backing> AsBytes for GenericNomBytes<B> {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        GenericNomBytes::as_bytes(self)
+    }
+}
+
+impl<B: Backing> InputIter for GenericNomBytes<B> {
+    type Item = u8;
+    type Iter = Enumerate<Self::IterElem>;
+    type IterElem = NomBytesIter<B>;
+
+    #[inline]
+    fn iter_indices(&self) -> Self::Iter {
+        self.iter_elements().enumerate()
+    }
+
+    #[inline]
+    fn iter_elements(&self) -> Self::IterElem {
+        self.clone().into_iter()
+    }
+
+    #[inline]
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.as_bytes().iter().position(|b| predicate(*b))
+    }
+
+    #[inline]
+    fn slice_index(&self, count: usize) -> Result<usize, nom::Needed> {
+        if self.as_bytes().len() >= count {
+            Ok(count)
+        } else {
+            Err(Needed::new(count - self.as_bytes().len()))
+        }
+    }
+}
+
+impl<B: Backing> InputTake for GenericNomBytes<B> {
+    #[inline]
+    fn take(&self, count: usize) -> Self {
+        self.slice(..count)
+    }
+
+    #[inline]
+    fn take_split(&self, count: usize) -> (Self, Self) {
+        let prefix = self.slice(..count);
+        let suffix = self.slice(count..);
+        (suffix, prefix)
+    }
+}
+
+impl<B: Backing> InputTakeAtPosition for GenericNomBytes<B> {
+    type Item = <Self as InputIter>::Item;
+
+    fn split_at_position<P, E: nom::error::ParseError<Self>>(
+        &self,
+        predicate: P,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        match self.as_bytes().iter().position(|c| predicate(*c)) {
+            Some(i) => Ok(self.take_split(i)),
+            None => Err(nom::Err::Incomplete(Needed::new(1))),
+        }
+    }
+
+    fn split_at_position1<P, E: nom::error::ParseError<Self>>(
+        &self,
+        predicate: P,
+        e: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        match self.as_bytes().iter().position(|c| predicate(*c)) {
+            Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+            Some(i) => Ok(self.take_split(i)),
+            None => Err(nom::Err::Incomplete(Needed::new(1))),
+        }
+    }
+
+    fn split_at_position_complete<P, E: nom::error::ParseError<Self>>(
+        &self,
+        predicate: P,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        match self.as_bytes().iter().position(|c| predicate(*c)) {
+            Some(i) => Ok(self.take_split(i)),
+            None => Ok(self.take_split(self.input_len())),
+        }
+    }
+
+    fn split_at_position1_complete<P, E: nom::error::ParseError<Self>>(
+        &self,
+        predicate: P,
+        e: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        let bytes = self.as_bytes();
+        match bytes.iter().position(|c| predicate(*c)) {
+            Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+            Some(i) => Ok(self.take_split(i)),
+            None => {
+                if bytes.is_empty() {
+                    Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+                } else {
+                    Ok(self.take_split(self.input_len()))
+                }
+            }
+        }
+    }
+}
+
+impl<B: Backing> InputLength for GenericNomBytes<B> {
+    #[inline]
+    fn input_len(&self) -> usize {
+        self.as_bytes().len()
+    }
+}
+
+macro_rules! nom_bytes_slice {
+    ($range_ty:ty) => {
+        impl<B: Backing> Slice<$range_ty> for GenericNomBytes<B> {
+            #[inline]
+            fn slice(&self, range: $range_ty) -> Self {
+                self.slice_range(range)
+            }
+        }
+    };
+}
+
+nom_bytes_slice!(Range<usize>);
+nom_bytes_slice!(RangeTo<usize>);
+nom_bytes_slice!(RangeFrom<usize>);
+nom_bytes_slice!(RangeFull);
+
+impl<B: Backing> Offset for GenericNomBytes<B> {
+    #[inline]
+    fn offset(&self, second: &Self) -> usize {
+        self.offset_to(second)
+    }
+}
+
+impl<B: Backing, C: Backing> Compare<GenericNomBytes<C>> for GenericNomBytes<B> {
+    #[inline]
+    fn compare(&self, t: GenericNomBytes<C>) -> nom::CompareResult {
+        self.as_bytes().compare(t.as_bytes())
+    }
+
+    #[inline]
+    fn compare_no_case(&self, t: GenericNomBytes<C>) -> nom::CompareResult {
+        self.as_bytes().compare_no_case(t.as_bytes())
+    }
+}
+
+impl<B: Backing> Compare<&'_ [u8]> for GenericNomBytes<B> {
+    #[inline]
+    fn compare(&self, t: &[u8]) -> nom::CompareResult {
+        self.as_bytes().compare(t)
+    }
+
+    #[inline]
+    fn compare_no_case(&self, t: &[u8]) -> nom::CompareResult {
+        self.as_bytes().compare_no_case(t)
+    }
+}
+
+impl<B: Backing> Compare<&'_ str> for GenericNomBytes<B> {
+    #[inline]
+    fn compare(&self, t: &str) -> nom::CompareResult {
+        self.as_bytes().compare(t.as_bytes())
+    }
+
+    #[inline]
+    fn compare_no_case(&self, t: &str) -> nom::CompareResult {
+        self.as_bytes().compare_no_case(t.as_bytes())
+    }
+}
+
+impl<B: Backing, C: Backing> FindSubstring<GenericNomBytes<C>> for GenericNomBytes<B> {
+    fn find_substring(&self, substr: GenericNomBytes<C>) -> Option<usize> {
+        self.as_bytes().find_substring(substr.as_bytes())
+    }
+}
+
+impl<B: Backing> FindSubstring<&'_ [u8]> for GenericNomBytes<B> {
+    fn find_substring(&self, substr: &[u8]) -> Option<usize> {
+        self.as_bytes().find_substring(substr)
+    }
+}
+
+impl<B: Backing> FindSubstring<&'_ str> for GenericNomBytes<B> {
+    fn find_substring(&self, substr: &str) -> Option<usize> {
+        self.as_bytes().find_substring(substr)
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{Backing, GenericNomBytes, NomBytesIter};
+use core::iter::Enumerate;
+use nom8::{AsBytes, Compare, CompareResult, FindSubstring, Input, Needed, Offset};
+
+// nom 8 folded `InputIter`, `InputTake`, `InputTakeAtPosition`, `InputLength`
+// and `Slice` into the single `Input` trait. The `split_at_position*` family
+// has default implementations in terms of `position` and `take_split`, which
+// are equivalent to what the nom 6/7 impls do by hand, so we rely on those.
+
+impl<B: Backing> AsBytes for GenericNomBytes<B> {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        GenericNomBytes::as_bytes(self)
+    }
+}
+
+impl<B: Backing> Input for GenericNomBytes<B> {
+    type Item = u8;
+    type Iter = NomBytesIter<B>;
+    type IterIndices = Enumerate<Self::Iter>;
+
+    #[inline]
+    fn input_len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    #[inline]
+    fn take(&self, index: usize) -> Self {
+        self.slice_range(..index)
+    }
+
+    #[inline]
+    fn take_from(&self, index: usize) -> Self {
+        self.slice_range(index..)
+    }
+
+    #[inline]
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        let prefix = self.slice_range(..index);
+        let suffix = self.slice_range(index..);
+        (suffix, prefix)
+    }
+
+    #[inline]
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.as_bytes().iter().position(|b| predicate(*b))
+    }
+
+    #[inline]
+    fn iter_elements(&self) -> Self::Iter {
+        self.clone().into_iter()
+    }
+
+    #[inline]
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.iter_elements().enumerate()
+    }
+
+    #[inline]
+    fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+        if self.as_bytes().len() >= count {
+            Ok(count)
+        } else {
+            Err(Needed::new(count - self.as_bytes().len()))
+        }
+    }
+}
+
+impl<B: Backing> Offset for GenericNomBytes<B> {
+    #[inline]
+    fn offset(&self, second: &Self) -> usize {
+        self.offset_to(second)
+    }
+}
+
+impl<B: Backing, C: Backing> Compare<GenericNomBytes<C>> for GenericNomBytes<B> {
+    #[inline]
+    fn compare(&self, t: GenericNomBytes<C>) -> CompareResult {
+        self.as_bytes().compare(t.as_bytes())
+    }
+
+    #[inline]
+    fn compare_no_case(&self, t: GenericNomBytes<C>) -> CompareResult {
+        self.as_bytes().compare_no_case(t.as_bytes())
+    }
+}
+
+impl<B: Backing> Compare<&'_ [u8]> for GenericNomBytes<B> {
+    #[inline]
+    fn compare(&self, t: &[u8]) -> CompareResult {
+        self.as_bytes().compare(t)
+    }
+
+    #[inline]
+    fn compare_no_case(&self, t: &[u8]) -> CompareResult {
+        self.as_bytes().compare_no_case(t)
+    }
+}
+
+impl<B: Backing> Compare<&'_ str> for GenericNomBytes<B> {
+    #[inline]
+    fn compare(&self, t: &str) -> CompareResult {
+        self.as_bytes().compare(t.as_bytes())
+    }
+
+    #[inline]
+    fn compare_no_case(&self, t: &str) -> CompareResult {
+        self.as_bytes().compare_no_case(t.as_bytes())
+    }
+}
+
+impl<B: Backing, C: Backing> FindSubstring<GenericNomBytes<C>> for GenericNomBytes<B> {
+    fn find_substring(&self, substr: GenericNomBytes<C>) -> Option<usize> {
+        self.as_bytes().find_substring(substr.as_bytes())
+    }
+}
+
+impl<B: Backing> FindSubstring<&'_ [u8]> for GenericNomBytes<B> {
+    fn find_substring(&self, substr: &[u8]) -> Option<usize> {
+        self.as_bytes().find_substring(substr)
+    }
+}
+
+impl<B: Backing> FindSubstring<&'_ str> for GenericNomBytes<B> {
+    fn find_substring(&self, substr: &str) -> Option<usize> {
+        self.as_bytes().find_substring(substr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::NomBytes;
+    use bytes::Bytes;
+    use nom8::bytes::complete::{tag, take_till};
+    use nom8::character::complete::digit1;
+    use nom8::{Input, Offset, Parser};
+
+    #[test]
+    fn it_works() {
+        let input = NomBytes::from(Bytes::from_static(
+            b"this is my cool input, please don't copy from me!",
+        ));
+
+        let (rest, v) = take_till::<_, _, nom8::error::Error<NomBytes>>(|v| v == b',')
+            .parse(input)
+            .unwrap();
+
+        assert_eq!(v.as_bytes(), b"this is my cool input");
+        assert_eq!(rest.as_bytes(), b", please don't copy from me!");
+    }
+
+    #[test]
+    fn combinators_work() {
+        let input = NomBytes::from("#1234;");
+
+        let (rest, (_, digits)) = (tag::<_, _, nom8::error::Error<NomBytes>>("#"), digit1)
+            .parse(input)
+            .unwrap();
+
+        assert_eq!(digits.as_bytes(), b"1234");
+        assert_eq!(rest.as_bytes(), b";");
+    }
+
+    #[test]
+    fn empty_slice_works() {
+        let nb = NomBytes::new(Bytes::from("hello"));
+        let (suffix, prefix) = nb.take_split(5);
+        assert_eq!(prefix.as_bytes(), b"hello");
+        assert_eq!(nb.offset(&suffix), 5);
+
+        // Even after re-slicing
+        let reslice = suffix.take_from(0);
+        assert_eq!(nb.offset(&reslice), 5);
+        assert_eq!(reslice.as_bytes(), b"");
+    }
+}
@@ -0,0 +1,711 @@
+use crate::{Backing, GenericNomBytes};
+use bytes::Bytes;
+use core::fmt::Display;
+use core::ops::{Range, RangeBounds};
+use core::str::Utf8Error;
+
+/// A borrowed, [`Copy`] view of a [`NomBytes`](crate::NomBytes), for parsers where even the
+/// reference count bump of slicing a `NomBytes` is too much.
+///
+/// A `NomBytesRef` implements the same `nom` traits as `NomBytes`, so a
+/// parser that is generic over its input runs on either one. Slicing it is
+/// nothing but integer arithmetic, and any slice of it can be turned back
+/// into an owned `NomBytes` sharing the same [`Bytes`] with
+/// [`to_owned_nombytes`](Self::to_owned_nombytes), e.g. when building an AST.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "nom7")]
+/// # {
+/// use nom::bytes::complete::{tag, take_while1};
+/// use nom::sequence::preceded;
+/// use nom::IResult;
+/// use nombytes::{NomBytes, NomBytesRef};
+///
+/// fn name(input: NomBytesRef<'_>) -> IResult<NomBytesRef<'_>, NomBytesRef<'_>> {
+///     preceded(tag("name: "), take_while1(|b: u8| b.is_ascii_alphabetic()))(input)
+/// }
+///
+/// let input = NomBytes::from("name: nombytes");
+/// let (_, found) = name(input.as_nom_bytes_ref()).unwrap();
+///
+/// // Drop the borrow, but keep the name and where it was
+/// let found: NomBytes = found.to_owned_nombytes();
+/// assert_eq!(found.as_bytes(), b"nombytes");
+/// assert_eq!(found.span(), 6..14);
+/// # }
+/// ```
+pub struct NomBytesRef<'a, B = Bytes> {
+    origin: &'a B,
+    start: usize,
+    end: usize,
+}
+
+// Deriving these would require `B: Clone` and `B: Copy`
+impl<B> Clone for NomBytesRef<'_, B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B> Copy for NomBytesRef<'_, B> {}
+
+impl<'a, B: Backing> NomBytesRef<'a, B> {
+    /// Creates a new `NomBytesRef` covering all of `bytes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use nombytes::NomBytesRef;
+    ///
+    /// let b = Bytes::from("hello");
+    /// let nbr = NomBytesRef::new(&b);
+    /// assert_eq!(nbr.as_bytes(), b"hello");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(bytes: &'a B) -> Self {
+        Self {
+            origin: bytes,
+            start: 0,
+            end: bytes.as_slice().len(),
+        }
+    }
+
+    /// Returns a byte slice to the contents of this view.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.origin.as_slice()[self.start..self.end]
+    }
+
+    /// Returns a string slice to the contents of this view.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the contents are not UTF-8 with a description as to
+    /// why they're not.
+    #[inline]
+    pub fn try_to_str(&self) -> Result<&'a str, Utf8Error> {
+        core::str::from_utf8(self.as_bytes())
+    }
+
+    /// Returns the range of its origin this view covers, like
+    /// [`GenericNomBytes::span`].
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the byte offset of `second` relative to `self`, or `None` if
+    /// `second` isn't a slice of `self`, like
+    /// [`GenericNomBytes::try_offset`].
+    #[must_use]
+    pub fn try_offset(&self, second: &Self) -> Option<usize> {
+        let fst = self.as_bytes().as_ptr() as usize;
+        let snd = second.as_bytes().as_ptr() as usize;
+
+        let offset = snd.checked_sub(fst)?;
+        (offset + second.as_bytes().len() <= self.as_bytes().len()).then(|| offset)
+    }
+
+    /// Returns an owned [`NomBytes`](crate::NomBytes) with the same
+    /// contents, which shares the [`Bytes`], or other [`Backing`], this view
+    /// borrows and has the same [`span`](GenericNomBytes::span) in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "nom7")]
+    /// # {
+    /// use nom::Slice;
+    /// use nombytes::NomBytes;
+    ///
+    /// let input = NomBytes::from("hello, world");
+    /// let world = input.as_nom_bytes_ref().slice(7..).to_owned_nombytes();
+    /// assert_eq!(world.as_bytes(), b"world");
+    /// assert_eq!(world.span(), 7..12);
+    /// assert!(world.same_origin(&input));
+    /// # }
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn to_owned_nombytes(&self) -> GenericNomBytes<B> {
+        GenericNomBytes::from_raw((self.origin.clone(), self.span()))
+    }
+
+    /// Slices this view. This is the implementation behind the `Slice` and
+    /// `Input` impls for the supported `nom` versions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, like slicing a `&[u8]` would.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    #[inline]
+    pub(crate) fn slice_range<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let len = self.end - self.start;
+        let range = crate::bounds(&range, len);
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {range:?} is out of bounds for a slice of length {len}"
+        );
+
+        Self {
+            origin: self.origin,
+            start: self.start + range.start,
+            end: self.start + range.end,
+        }
+    }
+
+    /// Returns the byte offset of `second` relative to `self`, which must be
+    /// a slice of `self`. Like `GenericNomBytes::offset_to`, this panics with
+    /// debug assertions enabled if it isn't.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    #[inline]
+    pub(crate) fn offset_to(&self, second: &Self) -> usize {
+        debug_assert!(
+            self.try_offset(second).is_some(),
+            "offset of a NomBytesRef that isn't a slice of this one: {second:?} isn't in {self:?}"
+        );
+        second.as_bytes().as_ptr() as usize - self.as_bytes().as_ptr() as usize
+    }
+}
+
+impl<B: Backing> GenericNomBytes<B> {
+    /// Returns a borrowed [`NomBytesRef`] view of this value, to parse
+    /// without bumping any reference counts.
+    #[inline]
+    #[must_use]
+    pub fn as_nom_bytes_ref(&self) -> NomBytesRef<'_, B> {
+        let span = self.span();
+        NomBytesRef {
+            origin: &self.origin,
+            start: span.start,
+            end: span.end,
+        }
+    }
+}
+
+impl<'a, B: Backing> From<&'a GenericNomBytes<B>> for NomBytesRef<'a, B> {
+    #[inline]
+    fn from(nb: &'a GenericNomBytes<B>) -> Self {
+        nb.as_nom_bytes_ref()
+    }
+}
+
+impl<'a, B: Backing> From<&'a B> for NomBytesRef<'a, B> {
+    #[inline]
+    fn from(bytes: &'a B) -> Self {
+        Self::new(bytes)
+    }
+}
+
+// Like `NomBytes`, only the contents and where they are in the origin.
+impl<B: Backing> core::fmt::Debug for NomBytesRef<'_, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("NomBytesRef")
+            .field(&crate::ByteStr(self.as_bytes()))
+            .field(&self.span())
+            .finish()
+    }
+}
+
+impl<B: Backing> Display for NomBytesRef<'_, B> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.try_to_str() {
+            Ok(s) => write!(f, "{s}"),
+            Err(e) => {
+                write!(f, "<{e}>")?;
+                Err(core::fmt::Error)
+            }
+        }
+    }
+}
+
+// Same as `NomBytes`, in terms of the contents.
+
+impl<B: Backing> PartialEq for NomBytesRef<'_, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes().eq(other.as_bytes())
+    }
+}
+impl<B: Backing> Eq for NomBytesRef<'_, B> {}
+
+impl<B: Backing> PartialOrd for NomBytesRef<'_, B> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<B: Backing> Ord for NomBytesRef<'_, B> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+#[cfg(feature = "nom7")]
+mod nom7 {
+    use super::NomBytesRef;
+    use crate::Backing;
+    use core::iter::{Copied, Enumerate};
+    use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
+    use nom::{
+        AsBytes, Compare, CompareResult, FindSubstring, InputIter, InputLength, InputTake,
+        InputTakeAtPosition, Needed, Offset, Slice,
+    };
+
+    impl<B: Backing> AsBytes for NomBytesRef<'_, B> {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            NomBytesRef::as_bytes(self)
+        }
+    }
+
+    impl<'a, B: Backing> InputIter for NomBytesRef<'a, B> {
+        type Item = u8;
+        type Iter = Enumerate<Self::IterElem>;
+        type IterElem = Copied<core::slice::Iter<'a, u8>>;
+
+        #[inline]
+        fn iter_indices(&self) -> Self::Iter {
+            self.iter_elements().enumerate()
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::IterElem {
+            NomBytesRef::as_bytes(self).iter().copied()
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            NomBytesRef::as_bytes(self)
+                .iter()
+                .position(|b| predicate(*b))
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            let len = NomBytesRef::as_bytes(self).len();
+            if len >= count {
+                Ok(count)
+            } else {
+                Err(Needed::new(count - len))
+            }
+        }
+    }
+
+    impl<B: Backing> InputTake for NomBytesRef<'_, B> {
+        #[inline]
+        fn take(&self, count: usize) -> Self {
+            self.slice_range(..count)
+        }
+
+        #[inline]
+        fn take_split(&self, count: usize) -> (Self, Self) {
+            let prefix = self.slice_range(..count);
+            let suffix = self.slice_range(count..);
+            (suffix, prefix)
+        }
+    }
+
+    impl<B: Backing> InputTakeAtPosition for NomBytesRef<'_, B> {
+        type Item = u8;
+
+        fn split_at_position<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position1<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: nom::error::ErrorKind,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(*self, e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position_complete<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Ok(self.take_split(self.input_len())),
+            }
+        }
+
+        fn split_at_position1_complete<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: nom::error::ErrorKind,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(*self, e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => {
+                    if self.input_len() == 0 {
+                        Err(nom::Err::Error(E::from_error_kind(*self, e)))
+                    } else {
+                        Ok(self.take_split(self.input_len()))
+                    }
+                }
+            }
+        }
+    }
+
+    impl<B: Backing> InputLength for NomBytesRef<'_, B> {
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.end - self.start
+        }
+    }
+
+    macro_rules! nom_bytes_ref_slice {
+        ($range_ty:ty) => {
+            impl<B: Backing> Slice<$range_ty> for NomBytesRef<'_, B> {
+                #[inline]
+                fn slice(&self, range: $range_ty) -> Self {
+                    self.slice_range(range)
+                }
+            }
+        };
+    }
+
+    nom_bytes_ref_slice!(Range<usize>);
+    nom_bytes_ref_slice!(RangeTo<usize>);
+    nom_bytes_ref_slice!(RangeFrom<usize>);
+    nom_bytes_ref_slice!(RangeFull);
+
+    impl<B: Backing> Offset for NomBytesRef<'_, B> {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.offset_to(second)
+        }
+    }
+
+    impl<B: Backing, C: Backing> Compare<NomBytesRef<'_, C>> for NomBytesRef<'_, B> {
+        #[inline]
+        fn compare(&self, t: NomBytesRef<'_, C>) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare(NomBytesRef::as_bytes(&t))
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: NomBytesRef<'_, C>) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare_no_case(NomBytesRef::as_bytes(&t))
+        }
+    }
+
+    impl<B: Backing> Compare<&'_ [u8]> for NomBytesRef<'_, B> {
+        #[inline]
+        fn compare(&self, t: &[u8]) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare(t)
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &[u8]) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare_no_case(t)
+        }
+    }
+
+    impl<B: Backing> Compare<&'_ str> for NomBytesRef<'_, B> {
+        #[inline]
+        fn compare(&self, t: &str) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare(t.as_bytes())
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &str) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare_no_case(t.as_bytes())
+        }
+    }
+
+    impl<B: Backing, C: Backing> FindSubstring<NomBytesRef<'_, C>> for NomBytesRef<'_, B> {
+        fn find_substring(&self, substr: NomBytesRef<'_, C>) -> Option<usize> {
+            NomBytesRef::as_bytes(self).find_substring(NomBytesRef::as_bytes(&substr))
+        }
+    }
+
+    impl<B: Backing> FindSubstring<&'_ [u8]> for NomBytesRef<'_, B> {
+        fn find_substring(&self, substr: &[u8]) -> Option<usize> {
+            NomBytesRef::as_bytes(self).find_substring(substr)
+        }
+    }
+
+    impl<B: Backing> FindSubstring<&'_ str> for NomBytesRef<'_, B> {
+        fn find_substring(&self, substr: &str) -> Option<usize> {
+            NomBytesRef::as_bytes(self).find_substring(substr)
+        }
+    }
+}
+
+#[cfg(feature = "nom8")]
+mod nom8 {
+    use super::NomBytesRef;
+    use crate::Backing;
+    use core::iter::{Copied, Enumerate};
+    use nom8::{AsBytes, Compare, CompareResult, FindSubstring, Input, Needed, Offset};
+
+    impl<B: Backing> AsBytes for NomBytesRef<'_, B> {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            NomBytesRef::as_bytes(self)
+        }
+    }
+
+    impl<'a, B: Backing> Input for NomBytesRef<'a, B> {
+        type Item = u8;
+        type Iter = Copied<core::slice::Iter<'a, u8>>;
+        type IterIndices = Enumerate<Self::Iter>;
+
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.end - self.start
+        }
+
+        #[inline]
+        fn take(&self, index: usize) -> Self {
+            self.slice_range(..index)
+        }
+
+        #[inline]
+        fn take_from(&self, index: usize) -> Self {
+            self.slice_range(index..)
+        }
+
+        #[inline]
+        fn take_split(&self, index: usize) -> (Self, Self) {
+            let prefix = self.slice_range(..index);
+            let suffix = self.slice_range(index..);
+            (suffix, prefix)
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            NomBytesRef::as_bytes(self)
+                .iter()
+                .position(|b| predicate(*b))
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::Iter {
+            NomBytesRef::as_bytes(self).iter().copied()
+        }
+
+        #[inline]
+        fn iter_indices(&self) -> Self::IterIndices {
+            self.iter_elements().enumerate()
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            let len = self.input_len();
+            if len >= count {
+                Ok(count)
+            } else {
+                Err(Needed::new(count - len))
+            }
+        }
+    }
+
+    impl<B: Backing> Offset for NomBytesRef<'_, B> {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.offset_to(second)
+        }
+    }
+
+    impl<B: Backing, C: Backing> Compare<NomBytesRef<'_, C>> for NomBytesRef<'_, B> {
+        #[inline]
+        fn compare(&self, t: NomBytesRef<'_, C>) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare(NomBytesRef::as_bytes(&t))
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: NomBytesRef<'_, C>) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare_no_case(NomBytesRef::as_bytes(&t))
+        }
+    }
+
+    impl<B: Backing> Compare<&'_ [u8]> for NomBytesRef<'_, B> {
+        #[inline]
+        fn compare(&self, t: &[u8]) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare(t)
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &[u8]) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare_no_case(t)
+        }
+    }
+
+    impl<B: Backing> Compare<&'_ str> for NomBytesRef<'_, B> {
+        #[inline]
+        fn compare(&self, t: &str) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare(t.as_bytes())
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &str) -> CompareResult {
+            NomBytesRef::as_bytes(self).compare_no_case(t.as_bytes())
+        }
+    }
+
+    impl<B: Backing, C: Backing> FindSubstring<NomBytesRef<'_, C>> for NomBytesRef<'_, B> {
+        fn find_substring(&self, substr: NomBytesRef<'_, C>) -> Option<usize> {
+            NomBytesRef::as_bytes(self).find_substring(NomBytesRef::as_bytes(&substr))
+        }
+    }
+
+    impl<B: Backing> FindSubstring<&'_ [u8]> for NomBytesRef<'_, B> {
+        fn find_substring(&self, substr: &[u8]) -> Option<usize> {
+            NomBytesRef::as_bytes(self).find_substring(substr)
+        }
+    }
+
+    impl<B: Backing> FindSubstring<&'_ str> for NomBytesRef<'_, B> {
+        fn find_substring(&self, substr: &str) -> Option<usize> {
+            NomBytesRef::as_bytes(self).find_substring(substr)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::NomBytes;
+        use nom8::bytes::complete::{tag, take_till};
+        use nom8::character::complete::digit1;
+        use nom8::{Input, Offset, Parser};
+
+        #[test]
+        fn parses_borrowed_input() {
+            let input = NomBytes::from("#1234;");
+            let view = input.as_nom_bytes_ref();
+
+            let (rest, (_, digits)) = (tag::<_, _, nom8::error::Error<_>>("#"), digit1)
+                .parse(view)
+                .unwrap();
+
+            assert_eq!(digits.as_bytes(), b"1234");
+            assert_eq!(digits.to_owned_nombytes().span(), 1..5);
+            assert_eq!(view.offset(&rest), 5);
+
+            let (_, prefix) = take_till::<_, _, ()>(|b| b == b';')
+                .parse(rest.take_from(1))
+                .unwrap();
+            assert_eq!(prefix.input_len(), 0);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "nom7"))]
+mod tests {
+    use super::NomBytesRef;
+    use crate::NomBytes;
+    use alloc::string::ToString;
+    use bytes::Bytes;
+    use core::ops::{Range, RangeFrom, RangeTo};
+    use nom::bytes::complete::{tag, take_until};
+    use nom::character::complete::digit1;
+    use nom::combinator::recognize;
+    use nom::error::ParseError;
+    use nom::sequence::{preceded, tuple};
+    use nom::{
+        Compare, FindSubstring, IResult, InputIter, InputLength, InputTake, InputTakeAtPosition,
+        Offset, Slice,
+    };
+
+    // Written once, for any input
+    fn version<I, E>(input: I) -> IResult<I, I, E>
+    where
+        I: Clone
+            + Offset
+            + InputLength
+            + InputTake
+            + InputIter<Item = u8>
+            + InputTakeAtPosition<Item = u8>
+            + Slice<Range<usize>>
+            + Slice<RangeFrom<usize>>
+            + Slice<RangeTo<usize>>
+            + for<'a> Compare<&'a str>
+            + for<'a> FindSubstring<&'a str>,
+        E: ParseError<I>,
+    {
+        preceded(
+            take_until("v"),
+            preceded(tag("v"), recognize(tuple((digit1, tag("."), digit1)))),
+        )(input)
+    }
+
+    #[test]
+    fn runs_the_same_parser_as_nombytes() {
+        let input = NomBytes::from("nombytes v0.1 (unreleased)");
+
+        let (rest, owned) = version::<_, ()>(input.clone()).unwrap();
+        let (rest_ref, borrowed) = version::<_, ()>(input.as_nom_bytes_ref()).unwrap();
+
+        assert_eq!(borrowed.as_bytes(), owned.as_bytes());
+        assert_eq!(borrowed.span(), owned.span());
+        assert_eq!(rest_ref.as_bytes(), rest.as_bytes());
+        assert_eq!(input.as_nom_bytes_ref().offset(&rest_ref), 13);
+    }
+
+    #[test]
+    fn upgrades_to_owned() {
+        let bytes = Bytes::from("key = value");
+        let value = NomBytesRef::new(&bytes).slice(6..);
+        let owned = value.to_owned_nombytes();
+
+        assert_eq!(owned.as_bytes(), b"value");
+        assert_eq!(owned.span(), 6..11);
+        assert_eq!(owned.origin().as_bytes(), b"key = value");
+        assert_eq!(owned.as_nom_bytes_ref(), value);
+        assert_eq!(owned.as_nom_bytes_ref().span(), value.span());
+
+        // Empty slices keep their place too
+        let end = value.slice(5..).to_owned_nombytes();
+        assert_eq!(end.span(), 11..11);
+        assert_eq!(NomBytes::new(bytes).try_offset(&end), Some(11));
+    }
+
+    #[test]
+    fn checks_offsets() {
+        let bytes = Bytes::from("hello, world");
+        let input = NomBytesRef::new(&bytes);
+        let other = Bytes::from("world");
+
+        assert_eq!(input.try_offset(&input.slice(7..)), Some(7));
+        assert_eq!(input.slice(7..).try_offset(&input), None);
+        assert_eq!(input.try_offset(&NomBytesRef::new(&other)), None);
+        assert_eq!(input.to_string(), "hello, world");
+    }
+}
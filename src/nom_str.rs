@@ -0,0 +1,781 @@
+use crate::NomBytes;
+use bytes::Bytes;
+use core::fmt::Display;
+use core::ops::{Index, RangeBounds};
+use core::str::Utf8Error;
+
+/// A wrapper around [`NomBytes`] whose contents are guaranteed to be valid
+/// UTF-8, to be able to use it with `nom` the same way you would use a
+/// `&str`.
+///
+/// Where [`NomBytes`] acts as `&[u8]`, `NomStr` acts as `&str`: iterating
+/// over it yields `char`s along with their byte indices, the
+/// `split_at_position` family takes `char` predicates, and slicing panics if
+/// it doesn't happen on a char boundary.
+// Deserialization goes through `TryFrom<NomBytes>`, which validates the UTF-8.
+#[allow(clippy::unsafe_derive_deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "NomBytes", try_from = "NomBytes"))]
+pub struct NomStr(NomBytes);
+
+impl NomStr {
+    /// Creates a new `NomStr` from the provided [`NomBytes`] if it contains
+    /// valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::{NomBytes, NomStr};
+    ///
+    /// let ns = NomStr::from_utf8(NomBytes::from("hello")).unwrap();
+    /// assert_eq!(ns.as_str(), "hello");
+    ///
+    /// assert!(NomStr::from_utf8(NomBytes::from(&b"\xff"[..])).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the [`NomBytes`] is not UTF-8 with a description
+    /// as to why the provided bytes are not UTF-8.
+    #[inline]
+    pub fn from_utf8(bytes: NomBytes) -> Result<Self, Utf8Error> {
+        bytes.try_to_str()?;
+        Ok(Self(bytes))
+    }
+
+    /// Converts a [`NomBytes`] into a `NomStr`, replacing any invalid UTF-8
+    /// sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// If the bytes are valid UTF-8, they are shared rather than copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::{NomBytes, NomStr};
+    ///
+    /// let ns = NomStr::from_utf8_lossy(NomBytes::from(&b"caf\xe9"[..]));
+    /// assert_eq!(ns.as_str(), "caf\u{fffd}");
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[must_use]
+    pub fn from_utf8_lossy(bytes: NomBytes) -> Self {
+        match String::from_utf8_lossy(bytes.as_bytes()) {
+            std::borrow::Cow::Borrowed(_) => Self(bytes),
+            std::borrow::Cow::Owned(string) => Self::from(string),
+        }
+    }
+
+    /// Returns a string slice to the contents of this `NomStr`.
+    ///
+    /// Unlike [`NomBytes::to_str`], this can't fail and doesn't re-validate
+    /// the contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomStr;
+    ///
+    /// let ns = NomStr::from("hello");
+    /// assert_eq!(ns.as_str(), "hello");
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // Validating here would make every parser step, which goes through
+        // this, cost as much as the whole remaining input.
+        #[allow(unsafe_code)]
+        // SAFETY: Every way of constructing a `NomStr` either validates the
+        // contents as UTF-8 or slices an existing `NomStr` on char boundaries
+        // (see `slice_range`), so the contents are always valid UTF-8.
+        unsafe {
+            core::str::from_utf8_unchecked(self.0.as_bytes())
+        }
+    }
+
+    /// Returns a byte slice to the contents of this `NomStr`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomStr;
+    ///
+    /// let ns = NomStr::from("hello");
+    /// assert_eq!(ns.as_bytes(), b"hello");
+    /// ```
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns a reference to the [`NomBytes`] this `NomStr` wraps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomStr;
+    ///
+    /// let ns = NomStr::from("hello");
+    /// assert_eq!(ns.as_nom_bytes().to_str(), "hello");
+    /// ```
+    #[inline]
+    pub fn as_nom_bytes(&self) -> &NomBytes {
+        &self.0
+    }
+
+    /// Returns the [`NomBytes`] this `NomStr` wraps. This is free, and the
+    /// returned value still knows where in the original input it came from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomStr;
+    ///
+    /// let ns = NomStr::from("hello");
+    /// let nb = ns.into_nom_bytes();
+    /// assert_eq!(nb.to_str(), "hello");
+    /// ```
+    #[inline]
+    pub fn into_nom_bytes(self) -> NomBytes {
+        self.0
+    }
+
+    #[doc = include_str!("to_bytes_doc.md")]
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomStr;
+    ///
+    /// let ns = NomStr::from("hello");
+    /// let b = ns.to_bytes();
+    /// assert_eq!(b.as_ref(), b"hello");
+    /// ```
+    #[inline]
+    pub fn to_bytes(&self) -> Bytes {
+        self.0.to_bytes()
+    }
+
+    #[doc = include_str!("to_bytes_doc.md")]
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomStr;
+    ///
+    /// let ns = NomStr::from("hello");
+    /// let b = ns.into_bytes();
+    /// assert_eq!(b.as_ref(), b"hello");
+    /// ```
+    #[inline]
+    pub fn into_bytes(self) -> Bytes {
+        self.0.into_bytes()
+    }
+
+    /// Slices this `NomStr` the same way indexing a `&str` would, with the
+    /// same empty-slice bookkeeping as [`NomBytes`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if either end of `range` is not on a char boundary, or is out
+    /// of bounds.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn slice_range<R>(&self, range: R) -> Self
+    where
+        R: RangeBounds<usize> + Clone,
+        str: Index<R, Output = str>,
+    {
+        // Let `str` do the boundary checking for us so we panic exactly
+        // when a `&str` would have.
+        let _ = &self.as_str()[range.clone()];
+        Self(self.0.slice_range(range))
+    }
+
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn chars(&self) -> NomStrChars {
+        NomStrChars {
+            ns: self.clone(),
+            pos: 0,
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn char_indices(&self) -> NomStrCharIndices {
+        NomStrCharIndices(self.chars())
+    }
+
+    /// The byte index of the `count`th char, mirroring `slice_index` for `&str`.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    pub(crate) fn char_slice_index(&self, count: usize) -> Option<usize> {
+        let s = self.as_str();
+        let mut cnt = 0;
+        for (index, _) in s.char_indices() {
+            if cnt == count {
+                return Some(index);
+            }
+            cnt += 1;
+        }
+        if cnt == count {
+            return Some(s.len());
+        }
+        None
+    }
+}
+
+/// An iterator over the `char`s of a [`NomStr`].
+///
+/// Holds on to its own clone of the [`NomStr`], so it's not tied to the
+/// lifetime of the value it was created from.
+#[derive(Clone, Debug)]
+pub struct NomStrChars {
+    ns: NomStr,
+    pos: usize,
+}
+
+impl Iterator for NomStrChars {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.ns.as_str()[self.pos..].chars().next()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ns.as_str()[self.pos..].chars().size_hint()
+    }
+}
+
+/// An iterator over the `char`s of a [`NomStr`] and their byte positions.
+///
+/// Holds on to its own clone of the [`NomStr`], so it's not tied to the
+/// lifetime of the value it was created from.
+#[derive(Clone, Debug)]
+pub struct NomStrCharIndices(NomStrChars);
+
+impl Iterator for NomStrCharIndices {
+    type Item = (usize, char);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.0.pos;
+        self.0.next().map(|c| (pos, c))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl Display for NomStr {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for NomStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&'_ str> for NomStr {
+    #[inline]
+    fn from(string: &'_ str) -> Self {
+        Self(NomBytes::from(string))
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl From<String> for NomStr {
+    #[inline]
+    fn from(string: String) -> Self {
+        Self(NomBytes::from(string))
+    }
+}
+
+impl TryFrom<NomBytes> for NomStr {
+    type Error = Utf8Error;
+
+    #[inline]
+    fn try_from(bytes: NomBytes) -> Result<Self, Self::Error> {
+        Self::from_utf8(bytes)
+    }
+}
+
+impl TryFrom<Bytes> for NomStr {
+    type Error = Utf8Error;
+
+    #[inline]
+    fn try_from(bytes: Bytes) -> Result<Self, Self::Error> {
+        Self::from_utf8(NomBytes::new(bytes))
+    }
+}
+
+impl From<NomStr> for NomBytes {
+    #[inline]
+    fn from(string: NomStr) -> Self {
+        string.0
+    }
+}
+
+#[cfg(feature = "nom7")]
+mod nom7 {
+    use super::{NomStr, NomStrCharIndices, NomStrChars};
+    use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
+    use core::str::FromStr;
+    use nom::{
+        AsBytes, Compare, FindSubstring, InputIter, InputLength, InputTake, InputTakeAtPosition,
+        Needed, Offset, ParseTo, Slice,
+    };
+
+    impl AsBytes for NomStr {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            NomStr::as_bytes(self)
+        }
+    }
+
+    impl InputIter for NomStr {
+        type Item = char;
+        type Iter = NomStrCharIndices;
+        type IterElem = NomStrChars;
+
+        #[inline]
+        fn iter_indices(&self) -> Self::Iter {
+            self.char_indices()
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::IterElem {
+            self.chars()
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            self.as_str().find(predicate)
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            self.char_slice_index(count).ok_or(Needed::Unknown)
+        }
+    }
+
+    impl InputTake for NomStr {
+        #[inline]
+        fn take(&self, count: usize) -> Self {
+            self.slice(..count)
+        }
+
+        #[inline]
+        fn take_split(&self, count: usize) -> (Self, Self) {
+            let prefix = self.slice(..count);
+            let suffix = self.slice(count..);
+            (suffix, prefix)
+        }
+    }
+
+    impl InputTakeAtPosition for NomStr {
+        type Item = char;
+
+        fn split_at_position<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.as_str().find(predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position1<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: nom::error::ErrorKind,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.as_str().find(predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position_complete<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.as_str().find(predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Ok(self.take_split(self.input_len())),
+            }
+        }
+
+        fn split_at_position1_complete<P, E: nom::error::ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: nom::error::ErrorKind,
+        ) -> nom::IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            let s = self.as_str();
+            match s.find(predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => {
+                    if s.is_empty() {
+                        Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+                    } else {
+                        Ok(self.take_split(self.input_len()))
+                    }
+                }
+            }
+        }
+    }
+
+    impl InputLength for NomStr {
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.as_str().len()
+        }
+    }
+
+    macro_rules! nom_str_slice {
+        ($range_ty:ty) => {
+            impl Slice<$range_ty> for NomStr {
+                #[inline]
+                fn slice(&self, range: $range_ty) -> Self {
+                    self.slice_range(range)
+                }
+            }
+        };
+    }
+
+    nom_str_slice!(Range<usize>);
+    nom_str_slice!(RangeTo<usize>);
+    nom_str_slice!(RangeFrom<usize>);
+    nom_str_slice!(RangeFull);
+
+    impl Offset for NomStr {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.as_bytes().offset(second.as_bytes())
+        }
+    }
+
+    impl Compare<NomStr> for NomStr {
+        #[inline]
+        fn compare(&self, t: NomStr) -> nom::CompareResult {
+            self.as_str().compare(t.as_str())
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: NomStr) -> nom::CompareResult {
+            self.as_str().compare_no_case(t.as_str())
+        }
+    }
+
+    impl Compare<&'_ str> for NomStr {
+        #[inline]
+        fn compare(&self, t: &str) -> nom::CompareResult {
+            self.as_str().compare(t)
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &str) -> nom::CompareResult {
+            self.as_str().compare_no_case(t)
+        }
+    }
+
+    impl FindSubstring<NomStr> for NomStr {
+        fn find_substring(&self, substr: NomStr) -> Option<usize> {
+            self.as_str().find_substring(substr.as_str())
+        }
+    }
+
+    impl FindSubstring<&'_ str> for NomStr {
+        fn find_substring(&self, substr: &str) -> Option<usize> {
+            self.as_str().find_substring(substr)
+        }
+    }
+
+    impl<R: FromStr> ParseTo<R> for NomStr {
+        #[inline]
+        fn parse_to(&self) -> Option<R> {
+            self.as_str().parse().ok()
+        }
+    }
+}
+
+#[cfg(feature = "nom8")]
+mod nom8 {
+    use super::{NomStr, NomStrCharIndices, NomStrChars};
+    use core::str::FromStr;
+    use nom8::{AsBytes, Compare, CompareResult, FindSubstring, Input, Needed, Offset, ParseTo};
+
+    impl AsBytes for NomStr {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            NomStr::as_bytes(self)
+        }
+    }
+
+    impl Input for NomStr {
+        type Item = char;
+        type Iter = NomStrChars;
+        type IterIndices = NomStrCharIndices;
+
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.as_str().len()
+        }
+
+        #[inline]
+        fn take(&self, index: usize) -> Self {
+            self.slice_range(..index)
+        }
+
+        #[inline]
+        fn take_from(&self, index: usize) -> Self {
+            self.slice_range(index..)
+        }
+
+        #[inline]
+        fn take_split(&self, index: usize) -> (Self, Self) {
+            let prefix = self.slice_range(..index);
+            let suffix = self.slice_range(index..);
+            (suffix, prefix)
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            self.as_str().find(predicate)
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::Iter {
+            self.chars()
+        }
+
+        #[inline]
+        fn iter_indices(&self) -> Self::IterIndices {
+            self.char_indices()
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            self.char_slice_index(count).ok_or(Needed::Unknown)
+        }
+    }
+
+    impl Offset for NomStr {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.as_bytes().offset(second.as_bytes())
+        }
+    }
+
+    impl Compare<NomStr> for NomStr {
+        #[inline]
+        fn compare(&self, t: NomStr) -> CompareResult {
+            self.as_str().compare(t.as_str())
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: NomStr) -> CompareResult {
+            self.as_str().compare_no_case(t.as_str())
+        }
+    }
+
+    impl Compare<&'_ str> for NomStr {
+        #[inline]
+        fn compare(&self, t: &str) -> CompareResult {
+            self.as_str().compare(t)
+        }
+
+        #[inline]
+        fn compare_no_case(&self, t: &str) -> CompareResult {
+            self.as_str().compare_no_case(t)
+        }
+    }
+
+    impl FindSubstring<NomStr> for NomStr {
+        fn find_substring(&self, substr: NomStr) -> Option<usize> {
+            self.as_str().find_substring(substr.as_str())
+        }
+    }
+
+    impl FindSubstring<&'_ str> for NomStr {
+        fn find_substring(&self, substr: &str) -> Option<usize> {
+            self.as_str().find_substring(substr)
+        }
+    }
+
+    impl<R: FromStr> ParseTo<R> for NomStr {
+        #[inline]
+        fn parse_to(&self) -> Option<R> {
+            self.as_str().parse().ok()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::NomStr;
+        use nom8::bytes::complete::take_while1;
+        use nom8::character::complete::{space1, u32};
+        use nom8::Parser;
+
+        #[test]
+        fn character_parsers_work() {
+            let input = NomStr::from("größe 42");
+
+            let (rest, (word, _, n)) = (
+                take_while1::<_, _, nom8::error::Error<NomStr>>(char::is_alphabetic),
+                space1,
+                u32,
+            )
+                .parse(input)
+                .unwrap();
+
+            assert_eq!(word.as_str(), "größe");
+            assert_eq!(n, 42);
+            assert_eq!(rest.as_str(), "");
+            assert!(
+                nom8::character::complete::char::<_, nom8::error::Error<NomStr>>('x')
+                    .parse(NomStr::from("ä"))
+                    .is_err()
+            );
+        }
+    }
+}
+
+#[cfg(all(test, feature = "nom7"))]
+mod tests {
+    use crate::{NomBytes, NomStr};
+    use bytes::Bytes;
+    use nom::bytes::complete::tag;
+    use nom::character::complete::{alpha1, anychar, char, digit1, multispace0, u32};
+    use nom::combinator::map_res;
+    use nom::multi::fold_many0;
+    use nom::sequence::{preceded, tuple};
+    use nom::{IResult, InputIter, Offset, Slice};
+
+    #[test]
+    fn character_parsers_work() {
+        fn key_value(input: NomStr) -> IResult<NomStr, (NomStr, u32)> {
+            let (input, key) = alpha1(input)?;
+            let (input, _) = tuple((multispace0, char('='), multispace0))(input)?;
+            let (input, value) = u32(input)?;
+            Ok((input, (key, value)))
+        }
+
+        let (rest, (key, value)) = key_value(NomStr::from("size = 42;")).unwrap();
+        assert_eq!(key.as_str(), "size");
+        assert_eq!(value, 42);
+        assert_eq!(rest.as_str(), ";");
+    }
+
+    #[test]
+    fn behaves_like_str() {
+        fn parse<I>(input: I) -> IResult<I, (char, I, char)>
+        where
+            I: Clone
+                + nom::InputIter<Item = char>
+                + nom::InputTakeAtPosition<Item = char>
+                + nom::InputTake
+                + nom::InputLength
+                + nom::Slice<core::ops::RangeFrom<usize>>
+                + for<'a> nom::Compare<&'a str>,
+        {
+            let (input, first) = anychar(input)?;
+            let (input, digits) = preceded(tag("#"), digit1)(input)?;
+            let (input, last) = anychar(input)?;
+            Ok((input, (first, digits, last)))
+        }
+
+        let src = "é#123ß rest";
+        let (str_rest, (a, str_digits, b)) = parse(src).unwrap();
+        let (ns_rest, (c, ns_digits, d)) = parse(NomStr::from(src)).unwrap();
+
+        assert_eq!((a, b), (c, d));
+        assert_eq!(str_digits, ns_digits.as_str());
+        assert_eq!(str_rest, ns_rest.as_str());
+    }
+
+    #[test]
+    fn iterates_chars_with_byte_indices() {
+        let ns = NomStr::from("aéb");
+        assert!(ns.iter_indices().eq([(0, 'a'), (1, 'é'), (3, 'b')]));
+        assert_eq!(ns.slice_index(2), Ok(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "char boundary")]
+    fn slicing_respects_char_boundaries() {
+        let ns = NomStr::from("é");
+        let _ = ns.slice(1..);
+    }
+
+    #[test]
+    fn empty_slice_works() {
+        let ns = NomStr::from("hello");
+        let empty = ns.slice(3..3);
+        assert_eq!(ns.offset(&empty), 3);
+        assert_eq!(ns.offset(&empty.slice(0..)), 3);
+        assert_eq!(empty.as_str(), "");
+    }
+
+    #[test]
+    fn converts_to_and_from_nom_bytes() {
+        let nb = NomBytes::from("hello");
+        let ns = NomStr::try_from(nb.clone()).unwrap();
+        assert_eq!(NomBytes::from(ns), nb);
+
+        let invalid = NomBytes::new(Bytes::from_static(b"\xc3\x28"));
+        assert!(NomStr::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn parses_large_inputs_char_by_char() {
+        // Each step has to be cheap, or this takes quadratic time.
+        let input = NomStr::try_from(Bytes::from("aé€".repeat(100_000))).unwrap();
+        let (rest, count) = fold_many0(
+            anychar::<_, nom::error::Error<NomStr>>,
+            || 0,
+            |count, _| count + 1,
+        )(input)
+        .unwrap();
+
+        assert_eq!(count, 300_000);
+        assert_eq!(rest.as_str(), "");
+    }
+
+    #[test]
+    fn works_with_map_res() {
+        let (_, n) = map_res(digit1::<_, nom::error::Error<NomStr>>, |d: NomStr| {
+            d.as_str().parse::<u8>()
+        })(NomStr::from("255"))
+        .unwrap();
+        assert_eq!(n, 255);
+    }
+}
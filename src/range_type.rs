@@ -1,7 +1,7 @@
 // We need this here because we can't set it on the output of the serde macros
 #![allow(clippy::type_repetition_in_bounds)]
 
-use core::ops::{Index, Range, RangeBounds, RangeFrom, RangeFull, RangeTo};
+use core::ops::{Range, RangeBounds, RangeFrom, RangeFull, RangeTo};
 
 /// An enum that can hold all the `Range*` types without being generic/trait
 /// based. We need this type because `SliceIndex<T>` is implemented for the
@@ -36,26 +36,6 @@ pub enum RangeType<T> {
     //RangeInclusive(RangeInclusive<T>),
 }
 
-impl<T: Clone> RangeType<T> {
-    // XXX: Disable clippy false positive
-    // <https://github.com/rust-lang/rust-clippy/issues/9076>
-    #[allow(clippy::trait_duplication_in_bounds)]
-    pub(crate) fn slice<'s, U>(&self, t: &'s U) -> &'s U
-    where
-        U: Index<RangeTo<T>, Output = U>
-            + Index<RangeFrom<T>, Output = U>
-            + Index<Range<T>, Output = U>
-            + ?Sized,
-    {
-        match self.clone() {
-            Self::RangeFull(_) => t,
-            Self::RangeTo(r) => &t[r],
-            Self::RangeFrom(r) => &t[r],
-            Self::Range(r) => &t[r],
-        }
-    }
-}
-
 impl<T> RangeBounds<T> for RangeType<T> {
     fn start_bound(&self) -> core::ops::Bound<&T> {
         match self {
@@ -95,65 +75,6 @@ range_to_from!(RangeTo);
 range_to_from!(RangeFrom);
 range_to_from!(Range);
 
-#[cfg(test)]
-mod tests {
-    use core::slice::SliceIndex;
-
-    use super::RangeType;
-
-    #[test]
-    fn slice() {
-        #[inline]
-        fn assert_slice(input: &[u8], rt: RangeType<usize>, expected: &[u8]) {
-            let output = rt.slice(input);
-
-            assert_eq!(output, expected);
-        }
-
-        #[inline]
-        fn assert_str(input: &str, rt: RangeType<usize>, expected: &str) {
-            let output = rt.slice(input);
-
-            assert_eq!(output, expected);
-        }
-
-        let input = b"hello, world!";
-        assert_slice(input, RangeType::RangeFull(..), input);
-        assert_slice(input, RangeType::RangeTo(..6), b"hello,");
-        assert_slice(input, RangeType::RangeFrom(7..), b"world!");
-        assert_slice(input, RangeType::Range(5..7), b", ");
-
-        let input = "hello, world!";
-        assert_str(input, RangeType::RangeFull(..), input);
-        assert_str(input, RangeType::RangeTo(..6), "hello,");
-        assert_str(input, RangeType::RangeFrom(7..), "world!");
-        assert_str(input, RangeType::Range(5..7), ", ");
-    }
-
-    #[test]
-    fn behaves_like_original_range() {
-        #[inline]
-        fn assert_identical_slice<T>(input: &str, range: T)
-        where
-            T: SliceIndex<str, Output = str> + Clone,
-            RangeType<usize>: From<T>,
-        {
-            let sliced = &input[range.clone()];
-
-            let range_type: RangeType<usize> = From::from(range);
-            let output = range_type.slice(input);
-
-            assert_eq!(sliced, output);
-        }
-
-        let input = "hello, world!";
-        assert_identical_slice(input, ..);
-        assert_identical_slice(input, ..6);
-        assert_identical_slice(input, 7..);
-        assert_identical_slice(input, 5..7);
-    }
-}
-
 #[cfg(feature = "serde")]
 mod range_type_serde {
     // A bug makes this seemingly both required and superfluous, but I can
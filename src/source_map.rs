@@ -0,0 +1,308 @@
+use crate::{LineIndex, NomBytes};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A collection of [`NomBytes`] sources laid out one after the other in a
+/// single, global offset space, much like `rustc`'s `SourceMap`.
+///
+/// Each source added to the map gets a range of global offsets of its own
+/// that doesn't overlap with any other source's, so a single `usize` (or a
+/// `Range<usize>`) is enough to point anywhere in any of them. Any slice of
+/// a source that was added can be resolved back to the source it came
+/// from, the range it covers there and the line and column it starts at.
+///
+/// There's a gap of one between the sources, so the empty slice at the end
+/// of one source doesn't share its global offset with the start of the
+/// next.
+///
+/// # Examples
+///
+/// ```
+/// use nombytes::{NomBytes, SourceMap};
+///
+/// let mut map = SourceMap::new();
+/// let a = NomBytes::from("first\nfile\n");
+/// let b = NomBytes::from("second\nfile\n");
+/// map.add("a.txt", a);
+/// let b_id = map.add("b.txt", b.clone());
+///
+/// // Imagine a parser handed us this slice of `b`
+/// let file = NomBytes::new(b.to_bytes().slice(7..11));
+///
+/// let location = map.lookup(&file).unwrap();
+/// assert_eq!(location.file, b_id);
+/// assert_eq!(location.range, 7..11);
+/// assert_eq!((location.line, location.column), (2, 1));
+///
+/// let span = map.span(&file).unwrap();
+/// assert_eq!(span, 19..23);
+/// assert_eq!(map.slice(span).unwrap().as_bytes(), b"file");
+/// ```
+#[derive(Clone, Debug)]
+pub struct SourceMap<Name = String> {
+    files: Vec<SourceFile<Name>>,
+}
+
+/// A source in a [`SourceMap`].
+#[derive(Clone, Debug)]
+pub struct SourceFile<Name = String> {
+    name: Name,
+    source: NomBytes,
+    start: usize,
+    lines: LineIndex,
+}
+
+/// Where a slice of a source in a [`SourceMap`] is, as returned by
+/// [`SourceMap::lookup`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// The id of the source the slice is in.
+    pub file: usize,
+    /// The byte range the slice covers in its source.
+    pub range: Range<usize>,
+    /// The 1-based line the slice starts on.
+    pub line: usize,
+    /// The 1-based column, in bytes, the slice starts at.
+    pub column: usize,
+}
+
+impl<Name> SourceFile<Name> {
+    /// Returns the name of the source.
+    #[inline]
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Returns the contents of the source.
+    #[inline]
+    pub fn source(&self) -> &NomBytes {
+        &self.source
+    }
+
+    /// Returns the global offsets the source covers.
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.start + self.source.as_bytes().len()
+    }
+
+    /// Returns the [`LineIndex`] of the source.
+    #[inline]
+    pub fn lines(&self) -> &LineIndex {
+        &self.lines
+    }
+
+    /// Returns the 1-based line and column, in bytes, of the given offset in
+    /// this source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the source.
+    #[must_use]
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        match self.lines.line_col(offset) {
+            Some(lc) => (lc.line, lc.column),
+            None => panic!("offset {offset} is out of bounds"),
+        }
+    }
+}
+
+impl<Name> SourceMap<Name> {
+    /// Creates a new, empty `SourceMap`.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self { files: Vec::new() }
+    }
+
+    /// Adds a source called `name` after the sources already in the map,
+    /// and returns the id it can be looked up by.
+    pub fn add(&mut self, name: Name, source: NomBytes) -> usize {
+        let start = self.files.last().map_or(0, |file| file.span().end + 1);
+        self.files.push(SourceFile {
+            name,
+            lines: LineIndex::new(&source),
+            source,
+            start,
+        });
+        self.files.len() - 1
+    }
+
+    /// Returns the source with the given id.
+    #[inline]
+    #[must_use]
+    pub fn file(&self, id: usize) -> Option<&SourceFile<Name>> {
+        self.files.get(id)
+    }
+
+    /// Returns an iterator over the sources in the map, in the order they
+    /// were added, which is also the order of their ids.
+    pub fn files(&self) -> impl Iterator<Item = &SourceFile<Name>> + '_ {
+        self.files.iter()
+    }
+
+    /// Returns the number of sources in the map.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if there are no sources in the map.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the id of the source `slice` is a slice of, together with the
+    /// byte range it covers in that source, or `None` if it isn't a slice of
+    /// any of them.
+    #[must_use]
+    pub fn find(&self, slice: &NomBytes) -> Option<(usize, Range<usize>)> {
+        self.files.iter().enumerate().find_map(|(id, file)| {
+            let start = file.source.try_offset(slice)?;
+            Some((id, start..start + slice.as_bytes().len()))
+        })
+    }
+
+    /// Returns the global offsets `slice` covers, or `None` if it isn't a
+    /// slice of any of the sources.
+    #[must_use]
+    pub fn span(&self, slice: &NomBytes) -> Option<Range<usize>> {
+        let (id, range) = self.find(slice)?;
+        let start = self.files[id].start;
+        Some(start + range.start..start + range.end)
+    }
+
+    /// Resolves `slice` to the source it is a slice of, the byte range it
+    /// covers there and the line and column it starts at, or `None` if it
+    /// isn't a slice of any of the sources.
+    #[must_use]
+    pub fn lookup(&self, slice: &NomBytes) -> Option<SourceLocation> {
+        let (file, range) = self.find(slice)?;
+        let (line, column) = self.files[file].line_col(range.start);
+        Some(SourceLocation {
+            file,
+            range,
+            line,
+            column,
+        })
+    }
+
+    /// Returns the id of the source the global offset `offset` is in,
+    /// together with the offset in that source, or `None` if no source
+    /// covers it.
+    ///
+    /// The offset just past the end of a source counts as being in it.
+    #[must_use]
+    pub fn lookup_offset(&self, offset: usize) -> Option<(usize, usize)> {
+        let id = match self.files.binary_search_by_key(&offset, |file| file.start) {
+            Ok(id) => id,
+            Err(0) => return None,
+            Err(next) => next - 1,
+        };
+        let file = &self.files[id];
+        (offset <= file.span().end).then(|| (id, offset - file.start))
+    }
+
+    /// Returns the slice of a source the global offsets in `span` cover, or
+    /// `None` if they don't all fall within a single source.
+    #[must_use]
+    pub fn slice(&self, span: Range<usize>) -> Option<NomBytes> {
+        let (id, start) = self.lookup_offset(span.start)?;
+        let file = &self.files[id];
+        let end = span.end.checked_sub(file.start)?;
+        (start <= end && end <= file.source.as_bytes().len())
+            .then(|| file.source.slice_range(start..end))
+    }
+}
+
+impl<Name> Default for SourceMap<Name> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SourceLocation, SourceMap};
+    use crate::NomBytes;
+
+    #[test]
+    fn assigns_disjoint_spans() {
+        let mut map = SourceMap::new();
+        assert!(map.is_empty());
+        let a = map.add("a", NomBytes::from("abc"));
+        let empty = map.add("empty", NomBytes::from(""));
+        let b = map.add("b", NomBytes::from("de"));
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.file(a).unwrap().span(), 0..3);
+        assert_eq!(map.file(empty).unwrap().span(), 4..4);
+        assert_eq!(map.file(b).unwrap().span(), 5..7);
+        assert!(map.file(3).is_none());
+        assert!(map.files().map(|file| *file.name()).eq(["a", "empty", "b"]));
+    }
+
+    #[test]
+    fn looks_up_slices() {
+        let a = NomBytes::from("one\ntwo\n");
+        let b = NomBytes::from("three\r\nfour");
+        let mut map = SourceMap::new();
+        map.add("a", a.clone());
+        map.add("b", b.clone());
+
+        assert_eq!(
+            map.lookup(&b.slice_range(7..)),
+            Some(SourceLocation {
+                file: 1,
+                range: 7..11,
+                line: 2,
+                column: 1
+            })
+        );
+        assert_eq!(map.span(&b.slice_range(7..)), Some(16..20));
+
+        // The empty slices at the end of a source still resolve to it
+        let end = a.slice_range(8..);
+        assert_eq!(map.find(&end), Some((0, 8..8)));
+        assert_eq!(map.lookup(&end).map(|l| (l.line, l.column)), Some((3, 1)));
+
+        assert_eq!(map.lookup(&NomBytes::from("one")), None);
+    }
+
+    #[test]
+    fn resolves_global_offsets() {
+        let mut map = SourceMap::new();
+        map.add("a", NomBytes::from("abc"));
+        map.add("b", NomBytes::from("def"));
+
+        assert_eq!(map.lookup_offset(0), Some((0, 0)));
+        assert_eq!(map.lookup_offset(3), Some((0, 3)));
+        assert_eq!(map.lookup_offset(4), Some((1, 0)));
+        assert_eq!(map.lookup_offset(7), Some((1, 3)));
+        assert_eq!(map.lookup_offset(8), None);
+
+        assert_eq!(map.slice(5..7).unwrap().as_bytes(), b"ef");
+        assert_eq!(map.slice(3..3).unwrap().as_bytes(), b"");
+        assert!(map.slice(2..5).is_none());
+        assert!(map.slice(5..9).is_none());
+        assert!(SourceMap::<&str>::new().lookup_offset(0).is_none());
+    }
+
+    #[test]
+    fn finds_lines_and_columns() {
+        let mut map = SourceMap::new();
+        let id = map.add("a", NomBytes::from("ab\n\ncd"));
+        let file = map.file(id).unwrap();
+
+        assert_eq!(file.line_col(0), (1, 1));
+        assert_eq!(file.line_col(2), (1, 3));
+        assert_eq!(file.line_col(3), (2, 1));
+        assert_eq!(file.line_col(5), (3, 2));
+        assert_eq!(file.line_col(6), (3, 3));
+    }
+}
@@ -0,0 +1,464 @@
+use crate::NomBytes;
+use bytes::Bytes;
+use core::fmt::Display;
+use nom::{IResult, Needed};
+use std::io::Read;
+
+/// How much we try to read from the reader at a time, unless the parser has
+/// told us it needs more than this.
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// An iterator that repeatedly runs a streaming `nom` parser over data read
+/// from a [`Read`], yielding the parsed items.
+///
+/// Data is read into a growing buffer that the parser sees as a
+/// [`NomBytes`]. Whenever the parser returns [`nom::Err::Incomplete`], more
+/// data is read, at least as much as the parser said it needed, and the
+/// parser is run again. Bytes left over after an item has been parsed are
+/// kept for the next one, and the parsed items share memory with the buffer,
+/// so nothing that has been parsed gets copied.
+///
+/// The iterator ends when the reader runs out of data at an item boundary.
+/// If it runs out in the middle of an item, or reading or parsing fails, the
+/// error is yielded and the iterator ends after that.
+///
+/// # Examples
+///
+/// ```
+/// use nom::bytes::streaming::{tag, take_until};
+/// use nom::sequence::terminated;
+/// use nom::IResult;
+/// use nombytes::{NomBytes, StreamParser};
+///
+/// fn line(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+///     terminated(take_until("\n"), tag("\n"))(input)
+/// }
+///
+/// let reader = std::io::Cursor::new("first\nsecond\n");
+/// let lines: Vec<_> = StreamParser::new(reader, line)
+///     .map(|line| line.unwrap().to_str().to_owned())
+///     .collect();
+///
+/// assert_eq!(lines, ["first", "second"]);
+/// ```
+#[derive(Debug)]
+pub struct StreamParser<R, P> {
+    reader: R,
+    parser: P,
+    // The bytes that have been read, but not parsed yet. They're in `buf`
+    // while more data is read in after them, and frozen into `remaining`
+    // while the parser runs. They stay there after it has parsed an item,
+    // since there's a good chance they hold more complete items, and go
+    // back to `buf` when it needs more data. Only one of the two is ever
+    // non-empty.
+    buf: Vec<u8>,
+    remaining: Bytes,
+    // The stream offset of the first unparsed byte.
+    position: usize,
+    // How many buffered bytes the parser needs before it's worth running it
+    // again.
+    needed: usize,
+    chunk_size: usize,
+    max_buffer_size: usize,
+    done: bool,
+}
+
+impl<R, P> StreamParser<R, P> {
+    /// Creates a new `StreamParser` that parses items from `reader` using
+    /// `parser`.
+    #[inline]
+    pub fn new(reader: R, parser: P) -> Self {
+        Self {
+            reader,
+            parser,
+            buf: Vec::new(),
+            remaining: Bytes::new(),
+            position: 0,
+            needed: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            max_buffer_size: usize::MAX,
+            done: false,
+        }
+    }
+
+    /// Sets how many bytes to try to read from the reader at a time, unless
+    /// the parser asks for more. Defaults to 8 KiB.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    #[must_use]
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be greater than 0");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the largest number of bytes the parser may need buffered to parse
+    /// a single item. If it asks for more than that, the iterator yields
+    /// [`StreamParserError::BufferLimitExceeded`] instead of reading on.
+    /// Defaults to no limit.
+    #[must_use]
+    pub fn with_max_buffer_size(mut self, max_buffer_size: usize) -> Self {
+        self.max_buffer_size = max_buffer_size;
+        self
+    }
+
+    /// Returns how many bytes of the stream have been consumed by parsed
+    /// items so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns a reference to the reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the reader, discarding any buffered bytes that haven't been
+    /// parsed yet.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// How many bytes are buffered, but not yet parsed.
+    #[inline]
+    fn buffered(&self) -> usize {
+        self.remaining.len() + self.buf.len()
+    }
+}
+
+impl<R: Read, P> StreamParser<R, P> {
+    /// Reads from the reader until at least `self.needed` bytes are buffered,
+    /// or at least once if that's already the case. Returns `false` if the
+    /// reader has run out of data.
+    fn fill(&mut self) -> std::io::Result<bool> {
+        let target = self.needed.max(self.buf.len() + 1);
+        while self.buf.len() < target {
+            let len = self.buf.len();
+            let want = self.chunk_size.max(target - len);
+            self.buf.resize(len + want, 0);
+
+            let result = self.reader.read(&mut self.buf[len..]);
+            let read = *result.as_ref().unwrap_or(&0);
+            self.buf.truncate(len + read);
+
+            match result {
+                Ok(0) => return Ok(false),
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl<R, P, O, E> Iterator for StreamParser<R, P>
+where
+    R: Read,
+    P: FnMut(NomBytes) -> IResult<NomBytes, O, E>,
+{
+    type Item = Result<O, StreamParserError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            if self.remaining.is_empty() {
+                if self.buf.is_empty() || self.buf.len() < self.needed {
+                    match self.fill() {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            self.done = true;
+                            return match self.buffered() {
+                                0 => None,
+                                len => Some(Err(StreamParserError::Incomplete {
+                                    offset: self.position,
+                                    len,
+                                })),
+                            };
+                        }
+                        Err(e) => {
+                            self.done = true;
+                            return Some(Err(StreamParserError::Io(e)));
+                        }
+                    }
+                    if self.buf.len() < self.needed {
+                        continue;
+                    }
+                }
+                self.remaining = Bytes::from(core::mem::take(&mut self.buf));
+            }
+
+            let input = NomBytes::new(core::mem::take(&mut self.remaining));
+            self.needed = 0;
+            match (self.parser)(input.clone()) {
+                Ok((rest, item)) => {
+                    self.position += input.offset_to(&rest);
+                    self.remaining = rest.into_bytes();
+                    return Some(Ok(item));
+                }
+                Err(nom::Err::Incomplete(needed)) => {
+                    self.needed = needed_len(input.as_bytes().len(), needed);
+                    if self.needed > self.max_buffer_size {
+                        self.done = true;
+                        return Some(Err(StreamParserError::BufferLimitExceeded {
+                            offset: self.position,
+                            needed: self.needed,
+                            limit: self.max_buffer_size,
+                        }));
+                    }
+                    self.buf = reclaim(input.into_bytes());
+                }
+                Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+                    self.done = true;
+                    return Some(Err(StreamParserError::Parse {
+                        offset: self.position,
+                        error,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+/// Turns bytes the parser needs more data after back into a buffer the data
+/// can be added to. Unless a parsed item still shares them, which can only
+/// happen the first time for any item, this reuses their memory rather than
+/// copying them.
+pub(crate) fn reclaim(bytes: Bytes) -> Vec<u8> {
+    Vec::from(bytes)
+}
+
+/// Returns how many bytes need to be buffered before it's worth running a
+/// parser again, after it returned `needed` for `len` bytes.
+pub(crate) fn needed_len(len: usize, needed: Needed) -> usize {
+    match needed {
+        Needed::Size(size) => len.saturating_add(size.get()),
+        Needed::Unknown => len + 1,
+    }
+}
+
+/// The error type of [`StreamParser`] and [`AsyncStreamParser`].
+///
+/// `E` is the error type of the parser.
+///
+/// [`AsyncStreamParser`]: crate::AsyncStreamParser
+#[derive(Debug)]
+pub enum StreamParserError<E = nom::error::Error<NomBytes>> {
+    /// Reading from the reader failed.
+    Io(std::io::Error),
+    /// The parser failed to parse the buffered bytes.
+    Parse {
+        /// The offset in the stream the item being parsed starts at.
+        ///
+        /// The parser was given the buffered bytes from there on as a fresh
+        /// [`NomBytes`], so positions in its error, like
+        /// [`NomBytes::offset_in_origin`] of the input it failed on, count
+        /// from this offset.
+        offset: usize,
+        /// The error the parser returned.
+        error: E,
+    },
+    /// The reader ran out of data in the middle of an item.
+    Incomplete {
+        /// The offset in the stream the unfinished item starts at.
+        offset: usize,
+        /// How many bytes of the unfinished item were read.
+        len: usize,
+    },
+    /// The parser needed more bytes buffered than the configured maximum
+    /// buffer size allows.
+    BufferLimitExceeded {
+        /// The offset in the stream the item being parsed starts at.
+        offset: usize,
+        /// How many bytes the parser needed buffered.
+        needed: usize,
+        /// The maximum buffer size.
+        limit: usize,
+    },
+}
+
+impl<E: Display> Display for StreamParserError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            StreamParserError::Io(e) => write!(f, "I/O error while parsing: {e}"),
+            StreamParserError::Parse { offset, error } => {
+                write!(f, "parse error in item at offset {offset}: {error}")
+            }
+            StreamParserError::Incomplete { offset, len } => write!(
+                f,
+                "stream ended in the middle of an item at offset {offset} ({len} bytes left)"
+            ),
+            StreamParserError::BufferLimitExceeded {
+                offset,
+                needed,
+                limit,
+            } => write!(
+                f,
+                "item at offset {offset} needs {needed} bytes buffered, but the limit is {limit}"
+            ),
+        }
+    }
+}
+
+impl<E> std::error::Error for StreamParserError<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamParserError::Io(e) => Some(e),
+            StreamParserError::Parse { error, .. } => Some(error),
+            StreamParserError::Incomplete { .. }
+            | StreamParserError::BufferLimitExceeded { .. } => None,
+        }
+    }
+}
+
+impl<E> From<std::io::Error> for StreamParserError<E> {
+    #[inline]
+    fn from(e: std::io::Error) -> Self {
+        StreamParserError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamParser, StreamParserError};
+    use crate::NomBytes;
+    use core::cell::Cell;
+    use nom::bytes::streaming::{tag, take_until};
+    use nom::error::ErrorKind;
+    use nom::multi::length_data;
+    use nom::number::streaming::be_u16;
+    use nom::sequence::terminated;
+    use nom::IResult;
+    use std::io::{Cursor, Read};
+
+    /// Hands out at most `max` bytes per read.
+    struct Trickle<R>(R, usize);
+
+    impl<R: Read> Read for Trickle<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let max = buf.len().min(self.1);
+            self.0.read(&mut buf[..max])
+        }
+    }
+
+    fn line(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+        terminated(take_until("\n"), tag("\n"))(input)
+    }
+
+    fn frame(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+        length_data(be_u16)(input)
+    }
+
+    #[test]
+    fn parses_items_across_reads() {
+        let reader = Trickle(Cursor::new("alpha\nbeta\n\ngamma\n"), 3);
+        let lines: Vec<_> = StreamParser::new(reader, line)
+            .with_chunk_size(2)
+            .map(|l| l.unwrap().to_str().to_owned())
+            .collect();
+
+        assert_eq!(lines, ["alpha", "beta", "", "gamma"]);
+    }
+
+    #[test]
+    fn reads_what_the_parser_needs_before_retrying() {
+        let calls = Cell::new(0);
+        let parser = |input: NomBytes| {
+            calls.set(calls.get() + 1);
+            frame(input)
+        };
+
+        let mut data = vec![0x01, 0x00];
+        data.extend(core::iter::repeat(b'x').take(256));
+        let reader = Trickle(Cursor::new(data), 16);
+
+        let mut items = StreamParser::new(reader, parser).with_chunk_size(2);
+        assert_eq!(items.next().unwrap().unwrap().as_bytes().len(), 256);
+        assert!(items.next().is_none());
+        // Once to learn the length and once more when the whole frame has
+        // arrived, rather than once for every read in between.
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn stops_when_the_parser_needs_too_much() {
+        let mut data = vec![0x00, 0x02, b'o', b'k', 0xff, 0xff];
+        data.extend(core::iter::repeat(b'x').take(64));
+        let mut items = StreamParser::new(Cursor::new(data), frame).with_max_buffer_size(16);
+
+        assert_eq!(items.next().unwrap().unwrap().to_str(), "ok");
+        assert!(matches!(
+            items.next(),
+            Some(Err(StreamParserError::BufferLimitExceeded {
+                offset: 4,
+                needed: 0x1_0001,
+                limit: 16,
+            }))
+        ));
+        assert!(items.next().is_none());
+    }
+
+    #[test]
+    fn reports_incomplete_item_at_eof() {
+        let mut lines = StreamParser::new(Cursor::new("one\ntwo"), line);
+
+        assert_eq!(lines.next().unwrap().unwrap().to_str(), "one");
+        assert!(matches!(
+            lines.next(),
+            Some(Err(StreamParserError::Incomplete { offset: 4, len: 3 }))
+        ));
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn reports_parse_errors_with_stream_offset() {
+        fn greeting(input: NomBytes) -> IResult<NomBytes, NomBytes> {
+            terminated(tag("hi"), tag("\n"))(input)
+        }
+
+        let mut items = StreamParser::new(Cursor::new("hi\nhi\nho\n"), greeting);
+
+        assert!(items.next().unwrap().is_ok());
+        assert!(items.next().unwrap().is_ok());
+        match items.next() {
+            Some(Err(StreamParserError::Parse { offset, error })) => {
+                assert_eq!(offset, 6);
+                assert_eq!(error.input.offset_in_origin(), 0);
+                assert_eq!(error.code, ErrorKind::Tag);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(items.next().is_none());
+        assert_eq!(items.position(), 6);
+    }
+
+    #[test]
+    fn accepts_other_error_types() {
+        fn greeting(input: NomBytes) -> IResult<NomBytes, NomBytes, crate::error::Error> {
+            terminated(tag("hi"), tag("\n"))(input)
+        }
+
+        let mut items = StreamParser::new(Cursor::new("hi\nho\n"), greeting);
+
+        assert!(items.next().unwrap().is_ok());
+        match items.next() {
+            Some(Err(StreamParserError::Parse { offset, error })) => {
+                assert_eq!(offset, 3);
+                assert_eq!(error.kind(), ErrorKind::Tag);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
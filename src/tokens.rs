@@ -0,0 +1,693 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{Range, RangeBounds};
+
+/// An input made up of a list of tokens, to parse the output of a lexer with
+/// `nom` the way [`NomBytes`](crate::NomBytes) parses bytes.
+///
+/// The tokens are stored once, in an `Arc<[T]>`, and slicing a `NomTokens`,
+/// which is what `nom` does whenever it takes some of the input, only
+/// narrows the range of them it covers. Like `NomBytes`, every slice
+/// remembers where it is in the list it was sliced out of, its
+/// [`span`](Self::span), even when it's empty.
+///
+/// `nom` hands out tokens by value, so iterating clones them. Tokens are
+/// usually small, and a token carrying its source text as a `NomBytes` slice
+/// of the original file is cheap to clone too.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "nom7")]
+/// # {
+/// use nom::bytes::complete::take;
+/// use nom::combinator::{map_opt, verify};
+/// use nom::sequence::{separated_pair, terminated};
+/// use nom::{IResult, Slice};
+/// use nombytes::{NomBytes, NomTokens};
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Token {
+///     Ident(NomBytes),
+///     Number(NomBytes),
+///     Eq,
+///     Semi,
+/// }
+///
+/// type Tokens = NomTokens<Token>;
+///
+/// fn one(input: Tokens) -> IResult<Tokens, Token> {
+///     map_opt(take(1usize), |t: Tokens| t.first().cloned())(input)
+/// }
+///
+/// fn punct(expected: Token) -> impl Fn(Tokens) -> IResult<Tokens, Token> {
+///     move |input| verify(one, |t: &Token| *t == expected)(input)
+/// }
+///
+/// fn ident(input: Tokens) -> IResult<Tokens, NomBytes> {
+///     map_opt(one, |t| match t {
+///         Token::Ident(name) => Some(name),
+///         _ => None,
+///     })(input)
+/// }
+///
+/// fn number(input: Tokens) -> IResult<Tokens, NomBytes> {
+///     map_opt(one, |t| match t {
+///         Token::Number(n) => Some(n),
+///         _ => None,
+///     })(input)
+/// }
+///
+/// fn assignment(input: Tokens) -> IResult<Tokens, (NomBytes, NomBytes)> {
+///     terminated(
+///         separated_pair(ident, punct(Token::Eq), number),
+///         punct(Token::Semi),
+///     )(input)
+/// }
+///
+/// // What a lexer would produce for this source
+/// let source = NomBytes::from("answer = 42;");
+/// let tokens = NomTokens::new(vec![
+///     Token::Ident(source.slice(0..6)),
+///     Token::Eq,
+///     Token::Number(source.slice(9..11)),
+///     Token::Semi,
+/// ]);
+///
+/// let (rest, (name, value)) = assignment(tokens).unwrap();
+/// assert_eq!(name.to_str(), "answer");
+/// assert_eq!(value.span(), 9..11);
+/// assert!(rest.is_empty());
+/// assert_eq!(rest.span(), 4..4);
+/// # }
+/// ```
+pub struct NomTokens<T> {
+    origin: Arc<[T]>,
+    start: usize,
+    end: usize,
+}
+
+impl<T> NomTokens<T> {
+    /// Creates a new `NomTokens` covering all of the provided tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nombytes::NomTokens;
+    ///
+    /// let tokens = NomTokens::new(vec!['a', 'b', 'c']);
+    /// assert_eq!(tokens.as_slice(), ['a', 'b', 'c']);
+    /// ```
+    #[inline]
+    pub fn new<I: Into<Arc<[T]>>>(tokens: I) -> Self {
+        let origin = tokens.into();
+        let end = origin.len();
+        Self {
+            origin,
+            start: 0,
+            end,
+        }
+    }
+
+    /// Returns the tokens this value covers.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        &self.origin[self.start..self.end]
+    }
+
+    /// Returns the number of tokens this value covers.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns `true` if this value covers no tokens.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the first token, or `None` if this value is empty.
+    #[inline]
+    #[must_use]
+    pub fn first(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    /// Returns the token at `index`, or `None` if it's out of bounds.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// Returns all the tokens this value was created with, or sliced out of,
+    /// as a `NomTokens` of its own.
+    #[inline]
+    #[must_use]
+    pub fn origin(&self) -> Self {
+        Self::new(Arc::clone(&self.origin))
+    }
+
+    /// Returns the range of token indices this value covers in its
+    /// [`origin`](Self::origin).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "nom7")]
+    /// # {
+    /// use nom::Slice;
+    /// use nombytes::NomTokens;
+    ///
+    /// let tokens = NomTokens::new(vec![1, 2, 3, 4]);
+    /// assert_eq!(tokens.slice(1..).slice(1..2).span(), 2..3);
+    /// # }
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns `true` if `self` and `other` were sliced out of the very same
+    /// list of tokens, even if they don't overlap.
+    #[inline]
+    #[must_use]
+    pub fn same_origin(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.origin, &other.origin)
+    }
+
+    /// Returns the number of tokens between the start of `self` and the start
+    /// of `second`, or `None` if `second` isn't a slice of `self`.
+    #[must_use]
+    pub fn try_offset(&self, second: &Self) -> Option<usize> {
+        (self.same_origin(second) && self.start <= second.start && second.end <= self.end)
+            .then(|| second.start - self.start)
+    }
+
+    /// Returns a new `NomTokens` covering `range` of this one, without
+    /// copying any tokens. This is the implementation behind the `Slice` and
+    /// `Input` impls for the supported `nom` versions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, like slicing a `&[T]` would.
+    #[must_use]
+    pub fn slice_range<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let len = self.len();
+        let range = crate::bounds(&range, len);
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {range:?} is out of bounds for a slice of length {len}"
+        );
+
+        Self {
+            origin: Arc::clone(&self.origin),
+            start: self.start + range.start,
+            end: self.start + range.end,
+        }
+    }
+
+    /// Returns the offset of `second` relative to `self`, in tokens. This is
+    /// the implementation behind the `Offset` impls for the supported `nom`
+    /// versions.
+    ///
+    /// With debug assertions enabled, this panics if `second` isn't a slice
+    /// of `self`, rather than returning garbage.
+    #[cfg_attr(not(any(feature = "nom7", feature = "nom8")), allow(dead_code))]
+    #[inline]
+    pub(crate) fn offset_to(&self, second: &Self) -> usize {
+        debug_assert!(
+            self.try_offset(second).is_some(),
+            "offset of a NomTokens that isn't a slice of this one: {:?} isn't in {:?}",
+            second.span(),
+            self.span(),
+        );
+        second.start - self.start
+    }
+}
+
+impl<T: Clone> NomTokens<T> {
+    /// Returns an iterator over clones of the tokens this value covers.
+    ///
+    /// To iterate over references instead, use
+    /// [`as_slice`](Self::as_slice)`.iter()`.
+    #[inline]
+    #[must_use]
+    pub fn iter(&self) -> NomTokensIter<T> {
+        NomTokensIter(self.clone())
+    }
+}
+
+// Not derived, so that `T` doesn't have to be `Clone` for the `Arc` to be.
+impl<T> Clone for NomTokens<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            origin: Arc::clone(&self.origin),
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+// Only the tokens and where they are in the origin, like `NomBytes`.
+impl<T: core::fmt::Debug> core::fmt::Debug for NomTokens<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("NomTokens")
+            .field(&self.as_slice())
+            .field(&self.span())
+            .finish()
+    }
+}
+
+impl<T> From<Vec<T>> for NomTokens<T> {
+    #[inline]
+    fn from(tokens: Vec<T>) -> Self {
+        Self::new(tokens)
+    }
+}
+
+impl<T> From<Arc<[T]>> for NomTokens<T> {
+    #[inline]
+    fn from(tokens: Arc<[T]>) -> Self {
+        Self::new(tokens)
+    }
+}
+
+impl<T> FromIterator<T> for NomTokens<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T> AsRef<[T]> for NomTokens<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+// Like `NomBytes`, equality and ordering are in terms of the contents.
+
+impl<T: PartialEq> PartialEq for NomTokens<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+impl<T: Eq> Eq for NomTokens<T> {}
+
+impl<T: PartialOrd> PartialOrd for NomTokens<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+impl<T: Ord> Ord for NomTokens<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Clone> IntoIterator for NomTokens<T> {
+    type Item = T;
+    type IntoIter = NomTokensIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> NomTokensIter<T> {
+        NomTokensIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a NomTokens<T> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+/// An iterator over clones of the tokens of a [`NomTokens`], which is what
+/// the `nom` impls hand out.
+///
+/// It holds on to a clone of the value it iterates over, so it doesn't
+/// borrow it.
+#[derive(Clone, Debug)]
+pub struct NomTokensIter<T>(NomTokens<T>);
+
+impl<T: Clone> Iterator for NomTokensIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let token = self.0.first()?.clone();
+        self.0.start += 1;
+        Some(token)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len();
+        (len, Some(len))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for NomTokensIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        let token = self.0.as_slice().last()?.clone();
+        self.0.end -= 1;
+        Some(token)
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for NomTokensIter<T> {}
+
+impl<T: Clone> core::iter::FusedIterator for NomTokensIter<T> {}
+
+#[cfg(feature = "nom7")]
+mod nom7 {
+    use super::{NomTokens, NomTokensIter};
+    use core::iter::Enumerate;
+    use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
+    use nom::error::{ErrorKind, ParseError};
+    use nom::{
+        IResult, InputIter, InputLength, InputTake, InputTakeAtPosition, Needed, Offset, Slice,
+    };
+
+    impl<T: Clone> InputIter for NomTokens<T> {
+        type Item = T;
+        type Iter = Enumerate<Self::IterElem>;
+        type IterElem = NomTokensIter<T>;
+
+        #[inline]
+        fn iter_indices(&self) -> Self::Iter {
+            self.iter().enumerate()
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::IterElem {
+            self.iter()
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            self.as_slice().iter().position(|t| predicate(t.clone()))
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            if self.len() >= count {
+                Ok(count)
+            } else {
+                Err(Needed::new(count - self.len()))
+            }
+        }
+    }
+
+    impl<T> InputTake for NomTokens<T> {
+        #[inline]
+        fn take(&self, count: usize) -> Self {
+            self.slice_range(..count)
+        }
+
+        #[inline]
+        fn take_split(&self, count: usize) -> (Self, Self) {
+            (self.slice_range(count..), self.slice_range(..count))
+        }
+    }
+
+    impl<T: Clone> InputTakeAtPosition for NomTokens<T> {
+        type Item = T;
+
+        fn split_at_position<P, E: ParseError<Self>>(&self, predicate: P) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position1<P, E: ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: ErrorKind,
+        ) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None => Err(nom::Err::Incomplete(Needed::new(1))),
+            }
+        }
+
+        fn split_at_position_complete<P, E: ParseError<Self>>(
+            &self,
+            predicate: P,
+        ) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(i) => Ok(self.take_split(i)),
+                None => Ok(self.take_split(self.len())),
+            }
+        }
+
+        fn split_at_position1_complete<P, E: ParseError<Self>>(
+            &self,
+            predicate: P,
+            e: ErrorKind,
+        ) -> IResult<Self, Self, E>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            match self.position(predicate) {
+                Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), e))),
+                Some(i) => Ok(self.take_split(i)),
+                None if self.is_empty() => {
+                    Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+                }
+                None => Ok(self.take_split(self.len())),
+            }
+        }
+    }
+
+    impl<T> InputLength for NomTokens<T> {
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.len()
+        }
+    }
+
+    macro_rules! nom_tokens_slice {
+        ($range_ty:ty) => {
+            impl<T> Slice<$range_ty> for NomTokens<T> {
+                #[inline]
+                fn slice(&self, range: $range_ty) -> Self {
+                    self.slice_range(range)
+                }
+            }
+        };
+    }
+
+    nom_tokens_slice!(Range<usize>);
+    nom_tokens_slice!(RangeTo<usize>);
+    nom_tokens_slice!(RangeFrom<usize>);
+    nom_tokens_slice!(RangeFull);
+
+    impl<T> Offset for NomTokens<T> {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.offset_to(second)
+        }
+    }
+}
+
+#[cfg(feature = "nom8")]
+mod nom8 {
+    use super::{NomTokens, NomTokensIter};
+    use core::iter::Enumerate;
+    use nom8::{Input, Needed, Offset};
+
+    impl<T: Clone> Input for NomTokens<T> {
+        type Item = T;
+        type Iter = NomTokensIter<T>;
+        type IterIndices = Enumerate<Self::Iter>;
+
+        #[inline]
+        fn input_len(&self) -> usize {
+            self.len()
+        }
+
+        #[inline]
+        fn take(&self, index: usize) -> Self {
+            self.slice_range(..index)
+        }
+
+        #[inline]
+        fn take_from(&self, index: usize) -> Self {
+            self.slice_range(index..)
+        }
+
+        #[inline]
+        fn take_split(&self, index: usize) -> (Self, Self) {
+            (self.slice_range(index..), self.slice_range(..index))
+        }
+
+        #[inline]
+        fn position<P>(&self, predicate: P) -> Option<usize>
+        where
+            P: Fn(Self::Item) -> bool,
+        {
+            self.as_slice().iter().position(|t| predicate(t.clone()))
+        }
+
+        #[inline]
+        fn iter_elements(&self) -> Self::Iter {
+            self.iter()
+        }
+
+        #[inline]
+        fn iter_indices(&self) -> Self::IterIndices {
+            self.iter().enumerate()
+        }
+
+        #[inline]
+        fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+            if self.len() >= count {
+                Ok(count)
+            } else {
+                Err(Needed::new(count - self.len()))
+            }
+        }
+    }
+
+    impl<T> Offset for NomTokens<T> {
+        #[inline]
+        fn offset(&self, second: &Self) -> usize {
+            self.offset_to(second)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::NomTokens;
+        use alloc::vec;
+        use nom8::bytes::complete::take_while1;
+        use nom8::{Offset, Parser};
+
+        #[test]
+        fn takes_tokens() {
+            let input = NomTokens::new(vec![1, 2, 3, 0, 4]);
+            let (rest, nonzero) = take_while1::<_, _, nom8::error::Error<_>>(|t| t != 0)
+                .parse(input.clone())
+                .unwrap();
+
+            assert_eq!(nonzero.as_slice(), [1, 2, 3]);
+            assert_eq!(input.offset(&rest), 3);
+            assert_eq!(rest.span(), 3..5);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "nom7"))]
+mod tests {
+    use crate::{NomBytes, NomTokens};
+    use alloc::vec::Vec;
+    use alloc::{format, vec};
+    use nom::bytes::complete::{take, take_while, take_while1};
+    use nom::combinator::{map_opt, verify};
+    use nom::multi::fold_many0;
+    use nom::sequence::{pair, preceded};
+    use nom::{IResult, Offset, Slice};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Token {
+        Word(NomBytes),
+        Comma,
+    }
+
+    type Tokens = NomTokens<Token>;
+
+    fn word(input: Tokens) -> IResult<Tokens, NomBytes> {
+        map_opt(take(1usize), |t: Tokens| match t.first()? {
+            Token::Word(word) => Some(word.clone()),
+            Token::Comma => None,
+        })(input)
+    }
+
+    fn comma(input: Tokens) -> IResult<Tokens, Tokens> {
+        verify(take(1usize), |t: &Tokens| t.first() == Some(&Token::Comma))(input)
+    }
+
+    #[test]
+    fn parses_tokens_pointing_into_their_source() {
+        let source = NomBytes::from("ab, cd,ef");
+        let input = NomTokens::new(vec![
+            Token::Word(source.slice(0..2)),
+            Token::Comma,
+            Token::Word(source.slice(4..6)),
+            Token::Comma,
+            Token::Word(source.slice(7..9)),
+        ]);
+
+        let words = fold_many0(preceded(comma, word), Vec::new, |mut words, word| {
+            words.push(word);
+            words
+        });
+        let (rest, (first, words)) = pair(word, words)(input.clone()).unwrap();
+
+        assert!(rest.is_empty());
+        assert_eq!(input.offset(&rest), 5);
+        assert_eq!(first.span(), 0..2);
+        let spans: Vec<_> = words.iter().map(NomBytes::span).collect();
+        assert_eq!(spans, [4..6, 7..9]);
+        assert!(words.iter().all(|w| w.same_origin(&source)));
+    }
+
+    #[test]
+    fn slices_keep_their_place() {
+        let input: NomTokens<u8> = (0..10).collect();
+        let (rest, small) =
+            take_while::<_, _, nom::error::Error<_>>(|t| t < 4)(input.clone()).unwrap();
+
+        assert_eq!(small.as_slice(), [0, 1, 2, 3]);
+        assert_eq!(rest.span(), 4..10);
+        assert_eq!(rest.slice(6..).span(), 10..10);
+        assert_eq!(rest.origin(), input);
+        assert_eq!(input.try_offset(&rest.slice(2..3)), Some(6));
+        assert_eq!(rest.try_offset(&input), None);
+        assert!(rest.iter().rev().eq((4..10).rev()));
+        assert_eq!(
+            format!("{:?}", small.slice(1..3)),
+            "NomTokens([1, 2], 1..3)"
+        );
+
+        let error = take_while1::<_, _, nom::error::Error<_>>(|t| t > 100)(input);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn slicing_past_the_end_panics() {
+        let input = NomTokens::new(vec![(); 3]);
+        let _ = input.slice(2..4);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{Backing, GenericNomBytes, NomBytesIter};
+use core::iter::Enumerate;
+use core::str::FromStr;
+use winnow::stream::{
+    AsBStr, AsBytes, Compare, CompareResult, FindSlice, Needed, Offset, ParseSlice, SliceLen,
+    Stream, StreamIsPartial, UpdateSlice,
+};
+
+// winnow's own `Checkpoint` can't be constructed outside of winnow, but a
+// `NomBytes` is cheap to clone and already knows how to compute offsets, so
+// it doubles as its own checkpoint. Slicing goes through `slice_range` like
+// the `nom` impls do, so empty slices keep their offsets, which is what
+// `LocatingSlice` relies on.
+
+impl<B: Backing> Stream for GenericNomBytes<B> {
+    type Token = u8;
+    type Slice = Self;
+    type IterOffsets = Enumerate<NomBytesIter<B>>;
+    type Checkpoint = Self;
+
+    #[inline]
+    fn iter_offsets(&self) -> Self::IterOffsets {
+        self.clone().into_iter().enumerate()
+    }
+
+    #[inline]
+    fn eof_offset(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    #[inline]
+    fn next_token(&mut self) -> Option<Self::Token> {
+        let token = *self.as_bytes().first()?;
+        *self = self.slice_range(1..);
+        Some(token)
+    }
+
+    #[inline]
+    fn peek_token(&self) -> Option<Self::Token> {
+        self.as_bytes().first().copied()
+    }
+
+    #[inline]
+    fn offset_for<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Token) -> bool,
+    {
+        self.as_bytes().iter().position(|b| predicate(*b))
+    }
+
+    #[inline]
+    fn offset_at(&self, tokens: usize) -> Result<usize, Needed> {
+        match tokens.checked_sub(self.as_bytes().len()) {
+            Some(needed) if needed > 0 => Err(Needed::new(needed)),
+            _ => Ok(tokens),
+        }
+    }
+
+    #[inline]
+    fn next_slice(&mut self, offset: usize) -> Self::Slice {
+        let slice = self.slice_range(..offset);
+        *self = self.slice_range(offset..);
+        slice
+    }
+
+    #[inline]
+    fn peek_slice(&self, offset: usize) -> Self::Slice {
+        self.slice_range(..offset)
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> Self::Checkpoint {
+        self.clone()
+    }
+
+    #[inline]
+    fn reset(&mut self, checkpoint: &Self::Checkpoint) {
+        *self = checkpoint.clone();
+    }
+
+    fn trace(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.as_bytes())
+    }
+}
+
+impl<B: Backing> StreamIsPartial for GenericNomBytes<B> {
+    type PartialState = ();
+
+    #[inline]
+    fn complete(&mut self) -> Self::PartialState {}
+
+    #[inline]
+    fn restore_partial(&mut self, _state: Self::PartialState) {}
+
+    #[inline]
+    fn is_partial_supported() -> bool {
+        false
+    }
+}
+
+impl<B: Backing> Offset for GenericNomBytes<B> {
+    #[inline]
+    fn offset_from(&self, start: &Self) -> usize {
+        start.offset_to(self)
+    }
+}
+
+impl<B: Backing> AsBytes for GenericNomBytes<B> {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        GenericNomBytes::as_bytes(self)
+    }
+}
+
+impl<B: Backing> AsBStr for GenericNomBytes<B> {
+    #[inline]
+    fn as_bstr(&self) -> &[u8] {
+        GenericNomBytes::as_bytes(self)
+    }
+}
+
+impl<B: Backing> SliceLen for GenericNomBytes<B> {
+    #[inline]
+    fn slice_len(&self) -> usize {
+        self.as_bytes().len()
+    }
+}
+
+// Anything a byte slice can be compared with or searched for, so can we.
+
+impl<T, B: Backing> Compare<T> for GenericNomBytes<B>
+where
+    for<'a> &'a [u8]: Compare<T>,
+{
+    #[inline]
+    fn compare(&self, t: T) -> CompareResult {
+        self.as_bytes().compare(t)
+    }
+}
+
+impl<T, B: Backing> FindSlice<T> for GenericNomBytes<B>
+where
+    for<'a> &'a [u8]: FindSlice<T>,
+{
+    #[inline]
+    fn find_slice(&self, substr: T) -> Option<core::ops::Range<usize>> {
+        self.as_bytes().find_slice(substr)
+    }
+}
+
+impl<R: FromStr, B: Backing> ParseSlice<R> for GenericNomBytes<B> {
+    #[inline]
+    fn parse_slice(&self) -> Option<R> {
+        self.try_to_str().ok().and_then(|s| s.parse().ok())
+    }
+}
+
+impl<B: Backing> UpdateSlice for GenericNomBytes<B> {
+    #[inline]
+    fn update_slice(self, inner: Self::Slice) -> Self {
+        inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::NomBytes;
+    use bytes::Bytes;
+    use winnow::binary::be_u16;
+    use winnow::error::{ContextError, ErrMode, Needed};
+    use winnow::stream::{LocatingSlice, Location, Offset, Partial, Stream};
+    use winnow::token::{literal, take_until, take_while};
+    use winnow::{ModalResult, Parser};
+
+    #[test]
+    fn it_works() {
+        fn key_value(input: &mut NomBytes) -> ModalResult<(NomBytes, NomBytes)> {
+            let key = take_while(1.., |b: u8| b.is_ascii_alphabetic()).parse_next(input)?;
+            literal("=").parse_next(input)?;
+            let value = take_until(0.., ";").parse_next(input)?;
+            Ok((key, value))
+        }
+
+        let mut input = NomBytes::from("name=ferris;rest");
+        let (key, value) = key_value(&mut input).unwrap();
+
+        assert_eq!(key.to_str(), "name");
+        assert_eq!(value.to_str(), "ferris");
+        assert_eq!(input.to_str(), ";rest");
+    }
+
+    #[test]
+    fn empty_slice_works() {
+        let nb = NomBytes::new(Bytes::from("hello"));
+        let mut rest = nb.clone();
+        let prefix = rest.next_slice(5);
+        assert_eq!(prefix.as_bytes(), b"hello");
+        assert_eq!(rest.offset_from(&nb), 5);
+
+        // Even after re-slicing
+        let reslice = rest.next_slice(0);
+        assert_eq!(reslice.offset_from(&nb), 5);
+        assert_eq!(reslice.as_bytes(), b"");
+    }
+
+    #[test]
+    fn works_with_locating_slice() {
+        let mut input = LocatingSlice::new(NomBytes::from("key=value"));
+        let _: NomBytes = take_until::<_, _, ContextError>(0.., "=")
+            .parse_next(&mut input)
+            .unwrap();
+        let eq = literal::<_, _, ContextError>("=")
+            .span()
+            .parse_next(&mut input)
+            .unwrap();
+        let _: NomBytes = take_while::<_, _, ContextError>(0.., |_| true)
+            .parse_next(&mut input)
+            .unwrap();
+
+        assert_eq!(eq, 3..4);
+        assert_eq!(input.current_token_start(), 9);
+    }
+
+    #[test]
+    fn works_with_partial() {
+        let mut input = Partial::new(NomBytes::from(&b"\x00"[..]));
+        assert_eq!(
+            be_u16::<_, ErrMode<ContextError>>.parse_next(&mut input),
+            Err(ErrMode::Incomplete(Needed::new(1)))
+        );
+
+        let mut input = Partial::new(NomBytes::from(&b"\x01\x02rest"[..]));
+        assert_eq!(
+            be_u16::<_, ErrMode<ContextError>>.parse_next(&mut input),
+            Ok(0x0102)
+        );
+        assert_eq!(input.into_inner().to_str(), "rest");
+    }
+}